@@ -0,0 +1,58 @@
+//! Plays classic beginner boards (9x9, 10 mines) without the terminal,
+//! looking only at what a player could see, and prints how many it won:
+//!
+//! ```text
+//! cargo run --example bot -- 100
+//! ```
+//!
+//! Each turn it flags the hidden cells a number must all be mines, opens
+//! the rest around a number that has all its flags, and otherwise reveals
+//! a random hidden cell.
+
+use std::env;
+
+use minesweeper::{strategy::Move, view::Seen, Game};
+use rand::seq::IndexedRandom;
+
+fn next_move(game: &Game) -> Option<Move> {
+    let view = game.board_view();
+    let mut hidden = Vec::new();
+    for y in 0..view.height {
+        for x in 0..view.width {
+            let Seen::Number(count) = view.get(x, y) else {
+                if view.get(x, y) == Seen::Hidden {
+                    hidden.push((x, y));
+                }
+                continue;
+            };
+            let flagged = game.neighbors(x, y).filter(|&(nx, ny)| matches!(view.get(nx, ny), Seen::Flagged | Seen::Mine)).count();
+            let unknown: Vec<_> = game.neighbors(x, y).filter(|&(nx, ny)| view.get(nx, ny) == Seen::Hidden).collect();
+            let Some(&(ux, uy)) = unknown.first() else { continue };
+            if flagged == count as usize {
+                return Some(Move::Reveal(ux, uy));
+            }
+            if flagged + unknown.len() == count as usize {
+                return Some(Move::Flag(ux, uy));
+            }
+        }
+    }
+    hidden.choose(&mut rand::rng()).map(|&(x, y)| Move::Reveal(x, y))
+}
+
+fn main() -> Result<(), String> {
+    let games: usize = match env::args().nth(1) {
+        Some(arg) => arg.parse().map_err(|_| format!("不是局数: {}", arg))?,
+        None => 10,
+    };
+    let mut won = 0;
+    for _ in 0..games {
+        let mut game = Game::new(9, 9, 10)?;
+        while !game.game_over && !game.victory {
+            let Some(next) = next_move(&game) else { break };
+            next.apply(&mut game);
+        }
+        won += game.victory as usize;
+    }
+    println!("{} 局中赢了 {} 局", games, won);
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::Game;
+
+/// Upper bound on the mine density, in percent of all cells. Expert is about
+/// 21%, so this leaves room to grow past it.
+const MAX_DENSITY_PERCENT: usize = 30;
+
+/// A win faster than this many seconds per safe cell counts as fast.
+const FAST_SECS_PER_CELL: f64 = 0.5;
+
+/// Adjusts the mine count between rounds of `--adaptive` practice:
+///
+/// * a loss removes one mine;
+/// * a win adds one mine, or two if it took less than
+///   `FAST_SECS_PER_CELL` seconds per safe cell;
+///
+/// always staying between 1 mine and `MAX_DENSITY_PERCENT` of the board.
+pub struct Adaptive {
+    pub mines: usize,
+}
+
+impl Adaptive {
+    pub fn new(mines: usize) -> Self {
+        Adaptive { mines }
+    }
+
+    /// Updates `mines` from a finished game. Unfinished games are ignored.
+    pub fn record(&mut self, game: &Game) {
+        let cells = game.width * game.height;
+        let safe = cells - game.mines;
+        if game.victory {
+            let fast = game.final_time() < safe as f64 * FAST_SECS_PER_CELL;
+            self.mines = game.mines + if fast { 2 } else { 1 };
+        } else if game.game_over {
+            self.mines = game.mines.saturating_sub(1);
+        } else {
+            return;
+        }
+        let max = (cells * MAX_DENSITY_PERCENT / 100).min(cells - 1).max(1);
+        self.mines = self.mines.clamp(1, max);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{Game, Symmetry, Topology, MAX_CLUSTERING};
+
+/// Crockford's base32 alphabet: no `I`, `L`, `O` or `U`, so a code read
+/// aloud or copied by hand is hard to get wrong.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Bumped whenever the byte layout or the board generator changes, so old
+/// codes are rejected instead of silently producing a different board.
+const VERSION: u8 = 1;
+
+/// Characters per dash-separated group in the printed code.
+const GROUP: usize = 5;
+
+/// Everything that decides where the mines go. Two games built from the same
+/// `BoardCode` have identical boards.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoardCode {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub seed: u64,
+    pub symmetry: Symmetry,
+    pub big_opening: bool,
+    pub safe_edge: bool,
+    /// In hundredths when encoded.
+    pub clustering: f64,
+    pub topology: Topology,
+}
+
+impl BoardCode {
+    pub fn of(game: &Game) -> Self {
+        BoardCode {
+            width: game.width,
+            height: game.height,
+            mines: game.mines,
+            seed: game.seed,
+            symmetry: game.config.symmetry,
+            big_opening: game.config.big_opening,
+            safe_edge: game.config.safe_edge,
+            clustering: game.config.clustering,
+            topology: game.config.topology,
+        }
+    }
+
+    /// The shareable form, in dash-separated groups of five characters. The
+    /// bytes are a version, the dimensions and seed as LEB128 varints, a
+    /// flags byte, the clustering factor if there is one, and a checksum.
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![VERSION];
+        for value in [self.width as u64, self.height as u64, self.mines as u64, self.seed] {
+            push_varint(&mut bytes, value);
+        }
+        let symmetry = match self.symmetry {
+            Symmetry::None => 0,
+            Symmetry::Horizontal => 1,
+            Symmetry::Vertical => 2,
+            Symmetry::Rotational => 3,
+        };
+        let clustering = (self.clustering * 100.0).round() as i64;
+        let hex = self.topology == Topology::Hex;
+        bytes.push(
+            symmetry
+                | (self.big_opening as u8) << 2
+                | ((clustering != 0) as u8) << 3
+                | (hex as u8) << 4
+                | (self.safe_edge as u8) << 5,
+        );
+        if clustering != 0 {
+            // Zigzag, so small negative factors stay short too.
+            push_varint(&mut bytes, (clustering << 1 ^ clustering >> 63) as u64);
+        }
+        bytes.push(checksum(&bytes));
+
+        let chars = to_base32(&bytes);
+        chars
+            .as_bytes()
+            .chunks(GROUP)
+            .map(|group| std::str::from_utf8(group).unwrap())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Parses a code made by `encode`. Case and dashes don't matter.
+    pub fn decode(code: &str) -> Result<Self, String> {
+        let invalid = || format!("无效的棋盘代码: {}", code);
+        let bytes = from_base32(code).ok_or_else(invalid)?;
+        let (sum, checked) = bytes.split_last().ok_or_else(invalid)?;
+        if checksum(checked) != *sum {
+            return Err(invalid());
+        }
+        let (&version, mut rest) = checked.split_first().ok_or_else(invalid)?;
+        if version != VERSION {
+            return Err(format!("棋盘代码版本不受支持: {}", version));
+        }
+        let mut fields = [0u64; 4];
+        for field in &mut fields {
+            *field = read_varint(&mut rest).ok_or_else(invalid)?;
+        }
+        let [width, height, mines, seed] = fields;
+        let (&flags, mut rest) = rest.split_first().ok_or_else(invalid)?;
+        let clustering = if flags & 0b1000 != 0 {
+            let zigzag = read_varint(&mut rest).ok_or_else(invalid)?;
+            ((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64)) as f64 / 100.0
+        } else {
+            0.0
+        };
+        if !rest.is_empty() || clustering.abs() > MAX_CLUSTERING {
+            return Err(invalid());
+        }
+        let symmetry = match flags & 0b11 {
+            0 => Symmetry::None,
+            1 => Symmetry::Horizontal,
+            2 => Symmetry::Vertical,
+            _ => Symmetry::Rotational,
+        };
+        if flags >> 6 != 0 || width == 0 || height == 0 || mines >= width.saturating_mul(height) {
+            return Err(invalid());
+        }
+        Ok(BoardCode {
+            width: width as usize,
+            height: height as usize,
+            mines: mines as usize,
+            seed,
+            symmetry,
+            big_opening: flags & 0b100 != 0,
+            safe_edge: flags & 0b10_0000 != 0,
+            clustering,
+            topology: if flags & 0b1_0000 != 0 { Topology::Hex } else { Topology::Square },
+        })
+    }
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// One byte of FNV-1a, enough to catch a mistyped character.
+fn checksum(bytes: &[u8]) -> u8 {
+    let hash = bytes.iter().fold(0x811c_9dc5u32, |hash, &byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193));
+    (hash ^ hash >> 8 ^ hash >> 16 ^ hash >> 24) as u8
+}
+
+fn to_base32(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = buffer << 8 | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[(buffer >> bits & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[(buffer << (5 - bits) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+fn from_base32(code: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in code.chars().filter(|&c| c != '-') {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let value = ALPHABET.iter().position(|&a| a as char == c)? as u32;
+        buffer = buffer << 5 | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code() -> BoardCode {
+        BoardCode {
+            width: 30,
+            height: 16,
+            mines: 99,
+            seed: 0x1234_5678_9abc_def0,
+            symmetry: Symmetry::None,
+            big_opening: false,
+            safe_edge: false,
+            clustering: 0.0,
+            topology: Topology::Square,
+        }
+    }
+
+    #[test]
+    fn every_setting_survives_the_round_trip() {
+        let symmetries = [Symmetry::None, Symmetry::Horizontal, Symmetry::Vertical, Symmetry::Rotational];
+        for symmetry in symmetries {
+            for topology in [Topology::Square, Topology::Hex] {
+                for (big_opening, safe_edge) in [(false, false), (true, false), (false, true), (true, true)] {
+                    for clustering in [0.0, 0.01, 1.5, -0.75, -MAX_CLUSTERING, MAX_CLUSTERING] {
+                        let original =
+                            BoardCode { symmetry, topology, big_opening, safe_edge, clustering, ..code() };
+                        assert_eq!(BoardCode::decode(&original.encode()), Ok(original));
+                    }
+                }
+            }
+        }
+        let tiny = BoardCode { width: 1, height: 2, mines: 0, seed: 0, ..code() };
+        assert_eq!(BoardCode::decode(&tiny.encode()), Ok(tiny));
+    }
+
+    #[test]
+    fn case_dashes_and_look_alike_letters_are_forgiven() {
+        let encoded = code().encode();
+        assert!(encoded.contains('-'));
+        assert!(encoded.contains('0') && encoded.contains('1'), "{}", encoded);
+        assert_eq!(BoardCode::decode(&encoded.to_lowercase()), Ok(code()));
+        assert_eq!(BoardCode::decode(&encoded.replace('-', "")), Ok(code()));
+        let misread = encoded.replace('0', "O").replace('1', "I");
+        assert_eq!(BoardCode::decode(&misread), Ok(code()));
+        assert_eq!(BoardCode::decode(&encoded.replace('1', "l")), Ok(code()));
+    }
+
+    #[test]
+    fn a_mistyped_character_fails_the_checksum() {
+        let encoded = code().encode();
+        // Inside the first group, well clear of the padding bits at the end.
+        let typo: String = encoded
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i == 2 { if c == 'Z' { 'Y' } else { 'Z' } } else { c })
+            .collect();
+        assert_eq!(BoardCode::decode(&typo), Err(format!("无效的棋盘代码: {}", typo)));
+        assert!(BoardCode::decode("").is_err());
+        assert!(BoardCode::decode("U").is_err());
+    }
+
+    #[test]
+    fn a_code_from_another_version_is_named_as_such() {
+        let mut bytes = vec![VERSION + 1];
+        for value in [8, 8, 10, 42] {
+            push_varint(&mut bytes, value);
+        }
+        bytes.push(0);
+        bytes.push(checksum(&bytes));
+        assert_eq!(BoardCode::decode(&to_base32(&bytes)), Err(format!("棋盘代码版本不受支持: {}", VERSION + 1)));
+    }
+
+    #[test]
+    fn a_board_without_a_safe_cell_is_rejected() {
+        for mines in [16, 17] {
+            let full = BoardCode { width: 4, height: 4, mines, ..code() };
+            assert!(BoardCode::decode(&full.encode()).is_err(), "{} mines", mines);
+        }
+        let one_safe = BoardCode { width: 4, height: 4, mines: 15, ..code() };
+        assert_eq!(BoardCode::decode(&one_safe.encode()), Ok(one_safe));
+    }
+}
@@ -0,0 +1,291 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use minesweeper::scores::config_dir;
+
+use crate::{
+    keys::KeyBindings,
+    theme::{PaletteChoice, THEME_NAMES},
+};
+
+/// Board shape of one difficulty preset.
+#[derive(Clone, Copy, Deserialize)]
+pub struct Preset {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+}
+
+/// A board listed in the difficulty menu: a built-in preset, or one added
+/// under `[[difficulties]]`.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+pub struct Difficulty {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+}
+
+impl Difficulty {
+    pub fn size(&self) -> (usize, usize, usize) {
+        (self.width, self.height, self.mines)
+    }
+}
+
+/// Replacements for the built-in presets. Any preset left out keeps its
+/// built-in size.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PresetOverrides {
+    pub beginner: Option<Preset>,
+    pub intermediate: Option<Preset>,
+    pub expert: Option<Preset>,
+}
+
+/// Which optional lines the end-of-game panel shows. The outcome, the time
+/// and the key hints are always there.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct EndScreen {
+    /// The best time for the difficulty, and whether this game set it.
+    pub best: bool,
+    /// The board code for sharing.
+    pub code: bool,
+    /// Safe cells revealed and flags placed.
+    pub progress: bool,
+    /// The board's 3BV, 3BV per second and 3BV per click.
+    pub efficiency: bool,
+}
+
+impl Default for EndScreen {
+    fn default() -> Self {
+        EndScreen { best: true, code: true, progress: true, efficiency: true }
+    }
+}
+
+/// Letters replacing those of the `--layout` profile, one per action, e.g.
+/// `flag = "x"`. Actions left out keep their key.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct KeyOverrides {
+    pub move_up: Option<String>,
+    pub move_down: Option<String>,
+    pub move_left: Option<String>,
+    pub move_right: Option<String>,
+    pub reveal: Option<String>,
+    pub flag: Option<String>,
+    pub chord: Option<String>,
+    pub restart: Option<String>,
+    pub quit: Option<String>,
+}
+
+impl KeyOverrides {
+    /// `keys` with the overrides applied. A value that isn't one character
+    /// is skipped with a warning; if two actions end up on the same key, none
+    /// of the overrides are used. Remapping a move key turns on letter
+    /// movement as `--vim` does.
+    pub fn apply(&self, keys: KeyBindings, warnings: &mut Vec<String>) -> KeyBindings {
+        let mut bound = keys;
+        let overrides = [
+            ("move_left", &self.move_left),
+            ("move_down", &self.move_down),
+            ("move_up", &self.move_up),
+            ("move_right", &self.move_right),
+            ("reveal", &self.reveal),
+            ("flag", &self.flag),
+            ("chord", &self.chord),
+            ("restart", &self.restart),
+            ("quit", &self.quit),
+        ];
+        for (index, (name, value)) in overrides.into_iter().enumerate() {
+            let Some(value) = value else { continue };
+            let mut chars = value.chars();
+            let key = match (chars.next(), chars.next()) {
+                (Some(key), None) if !key.is_ascii_digit() => key,
+                _ => {
+                    warnings.push(format!("按键 {} 无效 ({:?})，使用默认值", name, value));
+                    continue;
+                }
+            };
+            match index {
+                0..=3 => {
+                    bound.moves[index] = key;
+                    bound.vim = true;
+                }
+                4 => bound.reveal = key,
+                5 => bound.flag = key,
+                6 => bound.chord = key,
+                7 => bound.restart = key,
+                _ => bound.quit = key,
+            }
+        }
+        if let Some(key) = bound.clash() {
+            warnings.push(format!("按键 {:?} 被绑定了多次，[keys] 中的设置全部使用默认值", key));
+            return keys;
+        }
+        bound
+    }
+}
+
+/// What the settings screen changes, kept under `[settings]` and written
+/// back when the game exits. Flags on the command line can only turn these
+/// on for one run.
+#[derive(Clone, Copy, PartialEq, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub palette: PaletteChoice,
+    pub sound: bool,
+    /// Reveals open the 3x3 around the cell, as `--gentle`.
+    pub gentle: bool,
+    /// As `--strict-flags`.
+    pub strict_flags: bool,
+    /// Index into `theme::THEMES`, saved under the name `--theme` takes.
+    #[serde(with = "theme_name")]
+    pub theme: usize,
+}
+
+mod theme_name {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use super::THEME_NAMES;
+
+    pub fn serialize<S: Serializer>(index: &usize, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(THEME_NAMES[(*index).min(THEME_NAMES.len() - 1)])
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        THEME_NAMES
+            .iter()
+            .position(|&known| known == name)
+            .ok_or_else(|| D::Error::custom(format!("未知主题: {} (可选: {})", name, THEME_NAMES.join(", "))))
+    }
+}
+
+impl Settings {
+    /// Writes these settings into `config.toml`, keeping the rest of the
+    /// file's values. A file that doesn't parse is left alone.
+    pub fn save(&self) -> Result<(), String> {
+        let path = config_path().ok_or("找不到配置目录")?;
+        let mut table = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|err| format!("无法解析 {}: {}", path.display(), err))?,
+            Err(_) => toml::Table::new(),
+        };
+        let settings = toml::Value::try_from(self).map_err(|err| err.to_string())?;
+        table.insert("settings".to_string(), settings);
+        let text = toml::to_string(&table).map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        fs::write(&path, text).map_err(|err| format!("无法写入 {}: {}", path.display(), err))
+    }
+}
+
+/// Contents of `config.toml` in the config directory, for example:
+///
+/// ```toml
+/// [presets.expert]
+/// width = 30
+/// height = 16
+/// mines = 99
+///
+/// [[difficulties]]
+/// name = "Evil"
+/// width = 30
+/// height = 30
+/// mines = 200
+///
+/// [end_screen]
+/// code = false
+///
+/// [settings]
+/// palette = "truecolor"
+/// sound = true
+/// theme = "dark"
+///
+/// [keys]
+/// flag = "x"
+/// reveal = "z"
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub presets: PresetOverrides,
+    /// Boards listed in the menu after the built-in three.
+    pub difficulties: Vec<Difficulty>,
+    pub end_screen: EndScreen,
+    pub settings: Settings,
+    pub keys: KeyOverrides,
+}
+
+impl Config {
+    /// Loads the config file. A missing file is the default config; a file
+    /// that fails to parse is reported as a warning and otherwise ignored.
+    pub fn load() -> (Self, Vec<String>) {
+        let Some(path) = config_path() else {
+            return (Config::default(), Vec::new());
+        };
+        let Ok(text) = fs::read_to_string(&path) else {
+            return (Config::default(), Vec::new());
+        };
+        match toml::from_str(&text) {
+            Ok(config) => (config, Vec::new()),
+            Err(err) => (
+                Config::default(),
+                vec![format!("无法解析 {}: {}", path.display(), err)],
+            ),
+        }
+    }
+
+    /// The menu's boards: the three built-in `defaults` with any valid
+    /// overrides applied, then the `[[difficulties]]`. Invalid overrides and
+    /// entries, including boards over `max_cells`, are skipped with a
+    /// warning.
+    pub fn difficulties(
+        &self,
+        defaults: [Difficulty; 3],
+        max_cells: usize,
+        warnings: &mut Vec<String>,
+    ) -> Vec<Difficulty> {
+        let overrides = [
+            ("beginner", self.presets.beginner),
+            ("intermediate", self.presets.intermediate),
+            ("expert", self.presets.expert),
+        ];
+        let mut difficulties = defaults.to_vec();
+        for (slot, (name, preset)) in difficulties.iter_mut().zip(overrides) {
+            let Some(Preset { width, height, mines }) = preset else { continue };
+            if !playable((width, height, mines), max_cells) {
+                warnings.push(format!(
+                    "预设 {} 无效 ({}x{}, {} 雷)，使用默认值",
+                    name, width, height, mines
+                ));
+                continue;
+            }
+            *slot = Difficulty { width, height, mines, ..slot.clone() };
+        }
+        for added in &self.difficulties {
+            if added.name.trim().is_empty() || !playable(added.size(), max_cells) {
+                warnings.push(format!(
+                    "难度 {:?} 无效 ({}x{}, {} 雷)，已忽略",
+                    added.name, added.width, added.height, added.mines
+                ));
+                continue;
+            }
+            difficulties.push(added.clone());
+        }
+        difficulties
+    }
+}
+
+/// Whether a board of this size has cells, at most `max_cells`, and room
+/// for its mines.
+fn playable((width, height, mines): (usize, usize, usize), max_cells: usize) -> bool {
+    let cells = width.checked_mul(height).filter(|&cells| cells <= max_cells);
+    width > 0 && height > 0 && cells.is_some_and(|cells| mines < cells)
+}
+
+fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
@@ -0,0 +1,53 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{scores::civil_from_days, Game};
+
+/// The `--log` file, if one was opened. Everything here is a no-op without it.
+static LOG: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Opens `path` for appending; events from then on are written to it.
+pub fn open(path: &Path) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let _ = LOG.set(Mutex::new(file));
+    Ok(())
+}
+
+/// Writes one line, prefixed with the UTC time to the millisecond. A failed
+/// write is dropped: the log is for debugging and must never end a game.
+pub fn event(args: fmt::Arguments) {
+    let Some(log) = LOG.get() else { return };
+    let Ok(mut file) = log.lock() else { return };
+    let _ = writeln!(file, "{} {}", timestamp(), args);
+}
+
+/// A new board, with what it takes to build it again.
+pub fn board(game: &Game) {
+    event(format_args!(
+        "board {}x{} mines={} seed={}",
+        game.width, game.height, game.mines, game.seed
+    ));
+}
+
+fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = now.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        now.subsec_millis()
+    )
+}
@@ -0,0 +1,154 @@
+use std::fmt::Write;
+
+use tui::style::Color;
+
+use minesweeper::{topology::Topology, CellState, Game};
+
+use crate::{cell_color, cell_symbol};
+
+/// Side of one cell in the exported image, in SVG user units.
+const CELL_SIZE: usize = 24;
+
+/// Renders the board as it stands as a standalone SVG document: one `<rect>`
+/// per cell plus a `<text>` for every cell that shows a symbol, using the
+/// same symbols and colors as the terminal view.
+pub fn board_svg(game: &Game) -> String {
+    // Odd hex rows sit half a cell to the right, as on screen.
+    let stagger = match game.config.topology {
+        Topology::Square => 0,
+        Topology::Hex => CELL_SIZE / 2,
+    };
+    let (width, height) = (game.width * CELL_SIZE + stagger, game.height * CELL_SIZE);
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="{}"/>"#, width, height, hex(Color::Black));
+    let _ = writeln!(
+        svg,
+        r#"<g font-family="monospace" font-size="{}" font-weight="bold" text-anchor="middle" dominant-baseline="central">"#,
+        CELL_SIZE * 2 / 3
+    );
+    for (x, y, cell) in game.iter_cells() {
+        let (px, py) = (x * CELL_SIZE + stagger * (y % 2), y * CELL_SIZE);
+        let fill = match cell.state {
+            CellState::Hidden | CellState::Flagged | CellState::Questioned => Color::Gray,
+            CellState::Revealed => Color::DarkGray,
+        };
+        let _ = writeln!(
+            svg,
+            r#"<rect class="cell" x="{}" y="{}" width="{s}" height="{s}" fill="{}" stroke="{}"/>"#,
+            px,
+            py,
+            hex(fill),
+            hex(Color::Black),
+            s = CELL_SIZE
+        );
+        // Hidden cells are already told apart by their fill.
+        let symbol = cell_symbol(game, cell);
+        if cell.state == CellState::Hidden || symbol.trim().is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" fill="{}">{}</text>"#,
+            px + CELL_SIZE / 2,
+            py + CELL_SIZE / 2,
+            hex(cell_color(cell)),
+            symbol
+        );
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+/// RGB values for the terminal colors the board uses, close to the common
+/// xterm defaults.
+fn hex(color: Color) -> &'static str {
+    match color {
+        Color::Black => "#000000",
+        Color::Red => "#cd0000",
+        Color::Green => "#00cd00",
+        Color::Yellow => "#cdcd00",
+        Color::Blue => "#3b78ff",
+        Color::Magenta => "#cd00cd",
+        Color::Cyan => "#00cdcd",
+        Color::Gray => "#a0a0a0",
+        Color::DarkGray => "#4d4d4d",
+        Color::LightRed => "#ff5555",
+        Color::LightGreen => "#55ff55",
+        Color::LightYellow => "#ffff55",
+        Color::LightBlue => "#5c5cff",
+        Color::LightMagenta => "#ff55ff",
+        Color::LightCyan => "#55ffff",
+        _ => "#ffffff",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minesweeper::GameConfig;
+
+    use super::*;
+
+    /// The `width` the document declares.
+    fn svg_width(svg: &str) -> usize {
+        svg.split(r#"width=""#).nth(1).and_then(|rest| rest.split('"').next()).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn every_cell_gets_a_rect() {
+        for (width, height, mines) in [(9, 9, 10), (30, 16, 99), (1, 1, 0)] {
+            let svg = board_svg(&Game::new(width, height, mines).unwrap());
+            assert_eq!(svg.matches(r#"class="cell""#).count(), width * height);
+            assert_eq!(svg_width(&svg), width * CELL_SIZE);
+        }
+    }
+
+    #[test]
+    fn each_label_is_the_symbol_the_terminal_draws() {
+        // * 1 . .
+        // 1 1 . .
+        // . . 1 1
+        // . . 1 *
+        let mut game = Game::with_mines(4, 4, &[(0, 0), (3, 3)]).unwrap();
+        game.reveal(3, 0);
+        game.get_mut(0, 0).state = CellState::Flagged;
+        game.get_mut(3, 3).state = CellState::Questioned;
+        let svg = board_svg(&game);
+        let mut labelled = Vec::new();
+        for line in svg.lines().filter(|line| line.starts_with("<text ")) {
+            let number = |name: &str| -> usize {
+                line.split(&format!(r#"{}=""#, name)).nth(1).and_then(|rest| rest.split('"').next()).unwrap().parse().unwrap()
+            };
+            let (x, y) = (number("x") / CELL_SIZE, number("y") / CELL_SIZE);
+            let label = line.split('>').nth(1).and_then(|rest| rest.split('<').next()).unwrap();
+            assert_eq!(label, cell_symbol(&game, game.get(x, y)), "({}, {})", x, y);
+            labelled.push((x, y));
+        }
+        // Everything but the hidden cells and the blanks.
+        let shown: Vec<_> = game
+            .iter_cells()
+            .filter(|&(_, _, cell)| {
+                cell.state != CellState::Hidden && !(cell.state == CellState::Revealed && cell.neighbor_mines == 0)
+            })
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(labelled, shown);
+        assert!(shown.contains(&(0, 0)) && shown.contains(&(3, 3)) && shown.contains(&(1, 0)));
+    }
+
+    #[test]
+    fn hex_rows_are_staggered_by_half_a_cell() {
+        let game = Game::from_config(GameConfig::default().size(9, 4).mines(5).topology(Topology::Hex)).unwrap();
+        let svg = board_svg(&game);
+        assert_eq!(svg.matches(r#"class="cell""#).count(), 36);
+        assert_eq!(svg_width(&svg), 9 * CELL_SIZE + CELL_SIZE / 2);
+        assert!(svg.contains(r#"<rect class="cell" x="0" y="0""#));
+        assert!(svg.contains(&format!(r#"<rect class="cell" x="{}" y="{}""#, CELL_SIZE / 2, CELL_SIZE)));
+        assert!(svg.contains(&format!(r#"<rect class="cell" x="0" y="{}""#, 2 * CELL_SIZE)));
+    }
+}
@@ -0,0 +1,29 @@
+use std::mem;
+
+use crate::Game;
+
+/// Something that changed on the board. Actions queue these on the game
+/// instead of calling out, so whatever drives it (logging, sound, animation)
+/// drains them afterwards and reacts in one place.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameEvent {
+    /// A safe cell was opened, by a reveal or by a cascade.
+    Revealed(usize, usize),
+    /// A flag was placed (`true`) or taken off (`false`).
+    Flagged(usize, usize, bool),
+    /// A mine was stepped on, whether or not that ended the game.
+    MineHit(usize, usize),
+    GameOver,
+    Victory,
+}
+
+impl Game {
+    pub fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    /// The events since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        mem::take(&mut self.events)
+    }
+}
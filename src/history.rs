@@ -0,0 +1,142 @@
+use std::{fs, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{code::BoardCode, strategy::Move, Game};
+
+/// Every move made on a board with the time it was made, enough to rebuild
+/// the board at any point from its seed.
+#[derive(Clone)]
+pub struct History {
+    seed: u64,
+    pub moves: Vec<(Duration, Move)>,
+}
+
+impl History {
+    pub fn new(seed: u64) -> Self {
+        History { seed, moves: Vec::new() }
+    }
+}
+
+impl Game {
+    /// Notes a move about to be made, timed on the game clock.
+    pub fn record(&mut self, step: Move) {
+        let at = self.elapsed();
+        if let Some(history) = self.history.as_mut() {
+            history.moves.push((at, step));
+        }
+    }
+
+    /// The board as it stood after the first `count` recorded moves, built
+    /// again from the seed with the same rules. Cascades are run to the end
+    /// after each move. `None` for a game without a history (a resumed one).
+    pub fn replay_to(&self, count: usize) -> Option<Game> {
+        let history = self.history.as_ref()?;
+        let mut board = Game::from_config(self.config.clone().seed(history.seed)).ok()?;
+        for &(_, step) in history.moves.iter().take(count) {
+            step.execute(&mut board);
+            board.process_pending(usize::MAX);
+        }
+        Some(board)
+    }
+}
+
+/// A game's moves as `--record` writes them and `--replay` reads them back:
+/// the board as its board code, and every move with its time on the game
+/// clock in milliseconds. As in an autosave, the rules the moves were made
+/// under (`--gentle`, `--no-guess`, ...) are not stored and come from the
+/// command line of the replaying run.
+#[derive(Serialize, Deserialize)]
+pub struct Recording {
+    code: String,
+    moves: Vec<(u64, Move)>,
+}
+
+impl Recording {
+    /// `None` for a game without a history (a resumed one).
+    pub fn of(game: &Game) -> Option<Self> {
+        let history = game.history.as_ref()?;
+        let moves = history.moves.iter().map(|&(at, step)| (at.as_millis() as u64, step)).collect();
+        Some(Recording { code: BoardCode::of(game).encode(), moves })
+    }
+
+    /// Reads a recording and the board it was made on.
+    pub fn load(path: &Path) -> Result<(Self, BoardCode), String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("无法读取录像 {}: {}", path.display(), err))?;
+        let recording: Recording =
+            serde_json::from_str(&text).map_err(|err| format!("录像 {} 已损坏: {}", path.display(), err))?;
+        let code = BoardCode::decode(&recording.code)?;
+        let on_board = |&(_, step): &(u64, Move)| {
+            let (Move::Reveal(x, y) | Move::Flag(x, y) | Move::Chord(x, y) | Move::SafeReveal(x, y) | Move::UndoFlag(x, y)) =
+                step;
+            x < code.width && y < code.height
+        };
+        if !recording.moves.iter().all(on_board) {
+            return Err(format!("录像 {} 中有不在棋盘上的步骤", path.display()));
+        }
+        Ok((recording, code))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// The recorded moves as the history of `game`, a fresh board built from
+    /// the recording's code, so that `Review` can step through them.
+    pub fn attach(&self, game: &mut Game) {
+        let mut history = History::new(game.seed);
+        history.moves = self.moves.iter().map(|&(ms, step)| (Duration::from_millis(ms), step)).collect();
+        game.history = Some(history);
+    }
+}
+
+/// Stepping through a finished game one move at a time.
+pub struct Review {
+    /// Moves replayed onto `board`, from 0 (the empty board) to all of them.
+    pub step: usize,
+    pub total: usize,
+    pub board: Game,
+    /// When each move was made.
+    times: Vec<Duration>,
+}
+
+impl Review {
+    /// Starts at the final position.
+    pub fn new(game: &Game) -> Option<Self> {
+        let times: Vec<_> = game.history.as_ref()?.moves.iter().map(|&(at, _)| at).collect();
+        let total = times.len();
+        Some(Review { step: total, total, board: game.replay_to(total)?, times })
+    }
+
+    /// Starts at the empty board, for playing a `--replay` back.
+    pub fn from_start(game: &Game) -> Option<Self> {
+        let times: Vec<_> = game.history.as_ref()?.moves.iter().map(|&(at, _)| at).collect();
+        Some(Review { step: 0, total: times.len(), board: game.replay_to(0)?, times })
+    }
+
+    /// Moves on to the last step made by `elapsed` into the game, while
+    /// playing back. Never moves back.
+    pub fn play_to(&mut self, game: &Game, elapsed: Duration) {
+        let reached = self.times.iter().take_while(|&&at| at <= elapsed).count();
+        if reached > self.step {
+            self.seek(game, reached);
+        }
+    }
+
+    /// Moves to step `step`, clamped to the recorded moves.
+    pub fn seek(&mut self, game: &Game, step: usize) {
+        let step = step.min(self.total);
+        if step == self.step {
+            return;
+        }
+        if let Some(board) = game.replay_to(step) {
+            self.step = step;
+            self.board = board;
+        }
+    }
+
+    /// When the move that led to the current step was made.
+    pub fn time(&self) -> Duration {
+        self.step.checked_sub(1).map_or(Duration::ZERO, |i| self.times[i])
+    }
+}
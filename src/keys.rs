@@ -0,0 +1,131 @@
+use crossterm::event::KeyCode;
+
+/// Letters for the actions that have one. Arrow keys, Tab, Esc and the
+/// function keys are the same everywhere; `[keys]` in `config.toml` can move
+/// some of the others, space included.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct KeyBindings {
+    pub quit: char,
+    pub restart: char,
+    pub flag: char,
+    pub menu: char,
+    pub frontier: char,
+    pub select: char,
+    pub retry: char,
+    pub chord: char,
+    /// Space on every layout.
+    pub reveal: char,
+    pub pause: char,
+    pub auto_flag: char,
+    /// Writes the game to a file for `--resume <file>`.
+    pub save: char,
+    /// Shift and the save key's letter.
+    pub stats: char,
+    /// Takes back the last flag move.
+    pub undo: char,
+    /// Shift and the key in `l`'s spot, also without `--vim`.
+    pub leaderboard: char,
+    /// Shift and the frontier key: another board to play beside this one.
+    pub new_board: char,
+    /// Shows each hidden cell's chance of being a mine.
+    pub odds: char,
+    /// Lists every key and what it does; `?` on every layout.
+    pub help: char,
+    /// The hint key without and with `--vim`, which takes `h` for moving.
+    pub hints: [char; 2],
+    /// Left, down, up and right: `hjkl`, or the keys in their spot.
+    pub moves: [char; 4],
+    /// Whether `moves` move the cursor (`--vim`). Off by default, since on
+    /// Dvorak they would take keys that mean nothing there otherwise.
+    pub vim: bool,
+    /// Whether moving past an edge comes back in at the opposite one
+    /// (`--wrap`) instead of stopping there.
+    pub wrap: bool,
+}
+
+/// Names accepted by `--layout`.
+pub const LAYOUT_NAMES: [&str; 3] = ["qwerty", "azerty", "dvorak"];
+
+impl KeyBindings {
+    /// The profile for a keyboard layout. Each one puts every action on the
+    /// key in the same physical spot as on QWERTY, so the hands don't move.
+    pub fn for_layout(name: &str) -> Option<Self> {
+        let (quit, restart, flag, menu, frontier, select, retry, chord, pause, auto_flag, save, odds, undo, hints, moves) = match name {
+            "qwerty" => ('q', 'r', 'f', 'm', 'n', 'v', 't', 'c', 'p', 'a', 's', 'o', 'u', ['h', 'i'], ['h', 'j', 'k', 'l']),
+            "azerty" => ('a', 'r', 'f', ',', 'n', 'v', 't', 'c', 'p', 'q', 's', 'o', 'u', ['h', 'i'], ['h', 'j', 'k', 'l']),
+            "dvorak" => ('\'', 'p', 'u', 'm', 'b', 'k', 'y', 'j', 'l', 'a', 'o', 'r', 'g', ['d', 'c'], ['d', 'h', 't', 'n']),
+            _ => return None,
+        };
+        let (reveal, vim, wrap) = (' ', false, false);
+        Some(KeyBindings {
+            quit,
+            restart,
+            flag,
+            menu,
+            frontier,
+            select,
+            retry,
+            chord,
+            reveal,
+            pause,
+            auto_flag,
+            save,
+            stats: save.to_ascii_uppercase(),
+            leaderboard: moves[3].to_ascii_uppercase(),
+            new_board: frontier.to_ascii_uppercase(),
+            odds,
+            help: '?',
+            undo,
+            hints,
+            moves,
+            vim,
+            wrap,
+        })
+    }
+
+    pub fn hint(&self) -> char {
+        self.hints[self.vim as usize]
+    }
+
+    /// A key that two of the actions in use share, if any.
+    pub fn clash(&self) -> Option<char> {
+        let mut used = vec![
+            self.quit,
+            self.restart,
+            self.flag,
+            self.menu,
+            self.frontier,
+            self.select,
+            self.retry,
+            self.chord,
+            self.reveal,
+            self.pause,
+            self.auto_flag,
+            self.save,
+            self.stats,
+            self.leaderboard,
+            self.new_board,
+            self.odds,
+            self.help,
+            self.undo,
+            self.hint(),
+        ];
+        if self.vim {
+            used.extend(self.moves);
+        }
+        used.sort();
+        used.windows(2).find(|pair| pair[0] == pair[1]).map(|pair| pair[0])
+    }
+
+    /// The arrow key `c` stands for under `--vim`.
+    pub fn arrow(&self, c: char) -> Option<KeyCode> {
+        let index = self.moves.iter().position(|&m| m == c).filter(|_| self.vim)?;
+        Some([KeyCode::Left, KeyCode::Down, KeyCode::Up, KeyCode::Right][index])
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::for_layout("qwerty").unwrap()
+    }
+}
@@ -0,0 +1,1457 @@
+//! The game itself: boards, rules, solver and saves, with no terminal code.
+//! The `minesweeper` binary in `main.rs` is the front-end that draws it
+//! and turns keys into moves.
+
+pub mod adaptive;
+pub mod code;
+pub mod event_log;
+pub mod game_event;
+pub mod history;
+pub mod no_guess;
+pub mod save;
+pub mod scores;
+pub mod solver;
+pub mod stats;
+pub mod strategy;
+pub mod topology;
+pub mod view;
+
+use std::{
+    collections::VecDeque,
+    fmt, mem,
+    time::{Duration, Instant},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use game_event::GameEvent;
+use history::History;
+use no_guess::GenerationReport;
+use solver::Deduction;
+use strategy::Move;
+use topology::Topology;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CellState {
+    Hidden,
+    Revealed,
+    Flagged,
+    /// Marked `?` as a maybe: still hidden for every rule, and not a flag.
+    Questioned,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub is_mine: bool,
+    pub state: CellState,
+    pub neighbor_mines: u8,
+}
+
+impl Cell {
+    /// Whether the player treats this cell as a mine: it carries a flag, or it
+    /// is a mine that was already stepped on and left open.
+    pub fn marked_mine(&self) -> bool {
+        self.state == CellState::Flagged || (self.state == CellState::Revealed && self.is_mine)
+    }
+
+    /// Not opened and not flagged, with or without a `?`.
+    pub fn covered(&self) -> bool {
+        matches!(self.state, CellState::Hidden | CellState::Questioned)
+    }
+
+    /// A mine that is certain: one already stepped on and left open. Flags
+    /// are only the player's word for it.
+    pub fn known_mine(&self) -> bool {
+        self.state == CellState::Revealed && self.is_mine
+    }
+
+    /// Not opened, flagged or not. What the solver reasons about, since a
+    /// flag may be wrong.
+    pub fn unseen(&self) -> bool {
+        self.state != CellState::Revealed
+    }
+}
+
+/// Every knob that shapes a new game. Built with the chained setters below,
+/// starting from `GameConfig::default()`.
+#[derive(Clone)]
+pub struct GameConfig {
+    width: usize,
+    height: usize,
+    mines: usize,
+    /// Fixed seed for the mine layout; a random one is drawn when `None`.
+    seed: Option<u64>,
+    pub reveal_budget: usize,
+    /// Refuse to place more flags than there are mines.
+    strict_flag_limit: bool,
+    symmetry: Symmetry,
+    /// Keep the 3x3 around the first reveal free of mines so it always opens.
+    big_opening: bool,
+    /// Keep the top row and the left column free of mines, for a side to
+    /// start from that needs no luck.
+    safe_edge: bool,
+    pub mine_trigger: MineTrigger,
+    /// Every reveal also opens the eight cells around it.
+    pub gentle: bool,
+    /// Regenerate on the first reveal until the board needs no guessing.
+    no_guess: bool,
+    win_condition: WinCondition,
+    /// Bias of mine placement towards cells next to mines already placed:
+    /// positive clusters, negative spreads, zero is uniform.
+    clustering: f64,
+    pub topology: Topology,
+    /// Show the clock in the status bar and time the game.
+    pub timer: bool,
+    auto_first: AutoFirst,
+    /// A cell every game must start on, opened and timed from by itself.
+    first_cell: Option<FirstCell>,
+    /// Reveals per game that move a mine away instead of hitting it.
+    pub safe_reveals: u32,
+    /// Largest board `Game::from_config` agrees to allocate.
+    max_cells: usize,
+    /// Flag the mines left hidden as soon as the game is won.
+    auto_complete: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            width: 8,
+            height: 8,
+            mines: 10,
+            seed: None,
+            reveal_budget: DEFAULT_REVEAL_BUDGET,
+            strict_flag_limit: false,
+            symmetry: Symmetry::None,
+            big_opening: false,
+            safe_edge: false,
+            mine_trigger: MineTrigger::Lose,
+            gentle: false,
+            no_guess: false,
+            win_condition: WinCondition::RevealSafe,
+            clustering: 0.0,
+            topology: Topology::Square,
+            timer: true,
+            auto_first: AutoFirst::Off,
+            first_cell: None,
+            safe_reveals: 0,
+            max_cells: DEFAULT_MAX_CELLS,
+            auto_complete: false,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn mines(mut self, mines: usize) -> Self {
+        self.mines = mines;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn reveal_budget(mut self, budget: usize) -> Self {
+        self.reveal_budget = budget;
+        self
+    }
+
+    pub fn strict_flag_limit(mut self, strict: bool) -> Self {
+        self.strict_flag_limit = strict;
+        self
+    }
+
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    pub fn big_opening(mut self, big_opening: bool) -> Self {
+        self.big_opening = big_opening;
+        self
+    }
+
+    pub fn safe_edge(mut self, safe_edge: bool) -> Self {
+        self.safe_edge = safe_edge;
+        self
+    }
+
+    pub fn mine_trigger(mut self, mine_trigger: MineTrigger) -> Self {
+        self.mine_trigger = mine_trigger;
+        self
+    }
+
+    pub fn gentle(mut self, gentle: bool) -> Self {
+        self.gentle = gentle;
+        self
+    }
+
+    pub fn auto_complete(mut self, auto_complete: bool) -> Self {
+        self.auto_complete = auto_complete;
+        self
+    }
+
+    pub fn no_guess(mut self, no_guess: bool) -> Self {
+        self.no_guess = no_guess;
+        self
+    }
+
+    pub fn win_condition(mut self, win_condition: WinCondition) -> Self {
+        self.win_condition = win_condition;
+        self
+    }
+
+    pub fn clustering(mut self, clustering: f64) -> Self {
+        self.clustering = clustering;
+        self
+    }
+
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    fn timer(mut self, timer: bool) -> Self {
+        self.timer = timer;
+        self
+    }
+
+    pub fn max_cells(mut self, max_cells: usize) -> Self {
+        self.max_cells = max_cells;
+        self
+    }
+
+    pub fn auto_first(mut self, auto_first: AutoFirst) -> Self {
+        self.auto_first = auto_first;
+        self
+    }
+
+    pub fn first_cell(mut self, first_cell: Option<FirstCell>) -> Self {
+        self.first_cell = first_cell;
+        self
+    }
+
+    pub fn safe_reveals(mut self, safe_reveals: u32) -> Self {
+        self.safe_reveals = safe_reveals;
+        self
+    }
+
+    /// No clock and no way to lose, for playing without pressure.
+    pub fn zen(self) -> Self {
+        self.timer(false).mine_trigger(MineTrigger::Zen)
+    }
+}
+
+/// Whether a new board opens a starting cell by itself, saving the first
+/// click; done by `Game::open_start`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AutoFirst {
+    Off,
+    /// The clock still waits for the player's first reveal.
+    Waiting,
+    /// The clock starts with the automatic reveal.
+    Timed,
+}
+
+/// The fixed starting cell of `--first-cell`, so every player of a seed
+/// starts the same way and times compare.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FirstCell {
+    Center,
+    TopLeft,
+}
+
+impl FirstCell {
+    fn position(self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            FirstCell::Center => (width / 2, height / 2),
+            FirstCell::TopLeft => (0, 0),
+        }
+    }
+}
+
+impl std::str::FromStr for FirstCell {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "center" => Ok(FirstCell::Center),
+            "top-left" => Ok(FirstCell::TopLeft),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What wins a game; checked by `Game::check_victory`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum WinCondition {
+    /// Every safe cell is revealed.
+    RevealSafe,
+    /// Either every safe cell is revealed, or the flags cover exactly the
+    /// mines: every mine flagged (or already stepped on) and no flag on a
+    /// safe cell, so extra or wrong flags never win.
+    FlagMines,
+}
+
+/// What stepping on a mine does; applied by `Game::trigger_mine`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MineTrigger {
+    /// Any mine ends the game.
+    Lose,
+    /// Each mine costs one of `lives` and stays open on the board; the game
+    /// ends when the last life is lost.
+    Forgiving { lives: u32 },
+    /// Mines never end the game. Each one stepped on stays open and counts
+    /// as a mistake, and a board finished with mistakes is only a practice
+    /// completion, not a recorded win.
+    Practice,
+    /// Mines never end the game and aren't counted: one stepped on is
+    /// flagged instead of opened.
+    Zen,
+}
+
+impl MineTrigger {
+    /// Lives a new game starts with.
+    fn lives(self) -> u32 {
+        match self {
+            MineTrigger::Lose | MineTrigger::Practice | MineTrigger::Zen => 1,
+            MineTrigger::Forgiving { lives } => lives,
+        }
+    }
+}
+
+/// Mirror applied to every mine during generation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Symmetry {
+    None,
+    /// Left-right mirror across the vertical center line.
+    Horizontal,
+    /// Top-bottom mirror across the horizontal center line.
+    Vertical,
+    /// 180° rotation about the board center.
+    Rotational,
+}
+
+impl Symmetry {
+    fn mirror(self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Symmetry::None => (x, y),
+            Symmetry::Horizontal => (width - 1 - x, y),
+            Symmetry::Vertical => (x, height - 1 - y),
+            Symmetry::Rotational => (width - 1 - x, height - 1 - y),
+        }
+    }
+}
+
+impl std::str::FromStr for Symmetry {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Symmetry::None),
+            "horizontal" => Ok(Symmetry::Horizontal),
+            "vertical" => Ok(Symmetry::Vertical),
+            "rotational" => Ok(Symmetry::Rotational),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Game {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    /// Row by row, `width` cells each; `get` and `rows` index into it.
+    pub cells: Vec<Cell>,
+    pub cursor_x: usize,
+    pub cursor_y: usize,
+    pub game_over: bool,
+    pub victory: bool,
+    /// Set by `begin` on the first reveal or chord; flagging never starts
+    /// the clock.
+    pub start_time: Option<Instant>,
+    pub end_time: Option<Instant>,
+    /// When the clock was paused, if it is.
+    pub paused_at: Option<Instant>,
+    pub flags: usize,
+    pub seed: u64,
+    /// Cells waiting to be opened by an ongoing flood fill.
+    pub pending: VecDeque<(usize, usize)>,
+    pub config: GameConfig,
+    /// When a flag was last refused by the strict limit, for the UI flash.
+    pub flag_limit_hit: Option<Instant>,
+    /// Number of safe cells revealed so far, kept in step with the flood fill
+    /// so the victory check doesn't rescan the board.
+    pub revealed_safe: usize,
+    /// Lives left under `MineTrigger::Forgiving`.
+    pub lives: u32,
+    /// Mines the player stepped on, in order.
+    pub exploded: Vec<(usize, usize)>,
+    /// Cells opened by the guess assist (F9) rather than the player.
+    pub assisted_guesses: usize,
+    /// The safe cell the hint key last pointed out.
+    pub hint: Option<(usize, usize)>,
+    /// Hints asked for and found this game.
+    pub hints_used: usize,
+    /// Player moves that changed the board, for the efficiency on the end
+    /// screen. Moves on open cells, or chords that open nothing, don't count.
+    pub clicks: usize,
+    /// The cell the last move flagged or unflagged and its state before,
+    /// while nothing else has been done since; what the undo key puts back.
+    pub last_flag: Option<(usize, usize, CellState)>,
+    /// What is left of `GameConfig::safe_reveals` (F12).
+    pub safe_reveals: u32,
+    /// Set once a `no_guess` board has been generated.
+    pub generation: Option<GenerationReport>,
+    /// Corner where a rectangular selection started; the cursor is the
+    /// opposite corner.
+    pub selection: Option<(usize, usize)>,
+    /// Moves made so far; `None` when they aren't known, as for a game
+    /// resumed from an autosave.
+    history: Option<History>,
+    /// Changes not yet taken by `drain_events`.
+    events: Vec<GameEvent>,
+}
+
+impl Game {
+    pub fn new(width: usize, height: usize, mines: usize) -> Result<Self, String> {
+        Self::from_config(GameConfig::default().size(width, height).mines(mines))
+    }
+
+    /// A board without a single mine, under zen rules, for learning the
+    /// controls: the first reveal opens all of it and wins.
+    pub fn new_zen(width: usize, height: usize) -> Result<Self, String> {
+        Self::from_config(GameConfig::default().size(width, height).mines(0).zen())
+    }
+
+    /// A board with mines exactly at `mines`, for tutorials and scripted
+    /// games. Every coordinate must be on the board and appear once. The
+    /// layout has no seed, so the game can't be reviewed, and `reset` or
+    /// `retry` deal a random board of the same size and count.
+    pub fn with_mines(width: usize, height: usize, mines: &[(usize, usize)]) -> Result<Self, String> {
+        Self::with_mines_in(GameConfig::default().size(width, height), mines)
+    }
+
+    /// `with_mines` under the rules in `config`, on its board size.  Any
+    /// automatic opening is left to the boards after it: here it would run
+    /// before the mines are laid.
+    pub fn with_mines_in(config: GameConfig, mines: &[(usize, usize)]) -> Result<Self, String> {
+        let (width, height) = (config.width, config.height);
+        let empty = config.clone().mines(0).auto_first(AutoFirst::Off).first_cell(None);
+        let mut game = Self::from_config(empty)?;
+        for &(x, y) in mines {
+            if x >= width || y >= height {
+                return Err(format!("地雷 ({},{}) 不在 {}x{} 的棋盘上", x + 1, y + 1, width, height));
+            }
+            if game.get(x, y).is_mine {
+                return Err(format!("地雷 ({},{}) 重复", x + 1, y + 1));
+            }
+            game.get_mut(x, y).is_mine = true;
+        }
+        check_mines(width, height, mines.len())?;
+        game.mines = mines.len();
+        game.config = config.mines(mines.len());
+        game.history = None;
+        game.calculate_neighbors();
+        Ok(game)
+    }
+
+    /// The board drawn in `text`, as `to_ascii` writes it: `*` for a mine,
+    /// a digit, `+` or `.` for a safe cell, one line per row. The numbers
+    /// are not checked but worked out again from the mines, so a grid can be
+    /// drawn by hand with dots alone. Blank lines and trailing spaces are
+    /// ignored. Like `with_mines`, the rules are the defaults.
+    pub fn from_ascii(text: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = text.lines().map(str::trim_end).filter(|row| !row.is_empty()).collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err("棋盘是空的".to_string());
+        }
+        let mut mines = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!("第 {} 行有 {} 格，第 1 行有 {} 格", y + 1, row.chars().count(), width));
+            }
+            for (x, letter) in row.chars().enumerate() {
+                match letter {
+                    '*' => mines.push((x, y)),
+                    '0'..='9' | '+' | '.' => {}
+                    _ => return Err(format!("第 {} 行第 {} 格: 无法识别的字符 '{}'", y + 1, x + 1, letter)),
+                }
+            }
+        }
+        Self::with_mines(width, rows.len(), &mines)
+    }
+
+    /// A new board as `config` describes it. Boards over `max_cells` are
+    /// refused before anything is allocated, and so are boards without a
+    /// safe cell, which the mines could never be laid on.
+    pub fn from_config(config: GameConfig) -> Result<Self, String> {
+        let GameConfig { width, height, mines, seed, max_cells, safe_reveals, .. } = config;
+        if width.checked_mul(height).is_none_or(|cells| cells > max_cells) {
+            return Err(format!(
+                "棋盘 {}x{} 超过了 {} 格的上限 (可用 --max-cells 调整)",
+                width, height, max_cells
+            ));
+        }
+        check_mines(width, height, mines)?;
+        if config.safe_edge {
+            // Mirrored placement can't use the cells mirrored onto the edge
+            // either, and one cell off the edge stays free for the first
+            // reveal's mine to move to.
+            let room = (1..height)
+                .flat_map(|y| (1..width).map(move |x| (x, y)))
+                .filter(|&(x, y)| {
+                    let (mx, my) = config.symmetry.mirror(x, y, width, height);
+                    mx > 0 && my > 0
+                })
+                .count();
+            if mines > 0 && mines >= room {
+                return Err(format!(
+                    "空出第一行和第一列后, {}x{} 的棋盘最多只能有 {} 个地雷",
+                    width,
+                    height,
+                    room.saturating_sub(1)
+                ));
+            }
+        }
+        let lives = config.mine_trigger.lives();
+        let mut game = Game {
+            width,
+            height,
+            mines,
+            cells: vec![Cell { is_mine: false, state: CellState::Hidden, neighbor_mines: 0 }; width * height],
+            cursor_x: 0,
+            cursor_y: 0,
+            game_over: false,
+            victory: false,
+            start_time: None,
+            end_time: None,
+            paused_at: None,
+            flags: 0,
+            seed: seed.unwrap_or_else(rand::random),
+            pending: VecDeque::new(),
+            config,
+            flag_limit_hit: None,
+            revealed_safe: 0,
+            lives,
+            exploded: Vec::new(),
+            assisted_guesses: 0,
+            hint: None,
+            hints_used: 0,
+            clicks: 0,
+            last_flag: None,
+            safe_reveals,
+            generation: None,
+            selection: None,
+            history: None,
+            events: Vec::new(),
+        };
+        game.history = Some(History::new(game.seed));
+        game.place_mines();
+        game.calculate_neighbors();
+        game.open_start();
+        Ok(game)
+    }
+
+    /// Starts a new board with the same config in place, reusing the grid's
+    /// allocation. A config without a seed gets a fresh random board; one
+    /// with a seed gets the same board again.
+    pub fn reset(&mut self) {
+        self.reset_to(self.config.seed.unwrap_or_else(rand::random));
+    }
+
+    /// Starts this game's board over from its seed, whatever the config says.
+    pub fn retry(&mut self) {
+        self.reset_to(self.seed);
+    }
+
+    fn reset_to(&mut self, seed: u64) {
+        for cell in &mut self.cells {
+            *cell = Cell { is_mine: false, state: CellState::Hidden, neighbor_mines: 0 };
+        }
+        self.mines = self.config.mines;
+        self.seed = seed;
+        self.cursor_x = 0;
+        self.cursor_y = 0;
+        self.game_over = false;
+        self.victory = false;
+        self.start_time = None;
+        self.end_time = None;
+        self.paused_at = None;
+        self.flags = 0;
+        self.pending.clear();
+        self.flag_limit_hit = None;
+        self.revealed_safe = 0;
+        self.lives = self.config.mine_trigger.lives();
+        self.exploded.clear();
+        self.assisted_guesses = 0;
+        self.hint = None;
+        self.hints_used = 0;
+        self.clicks = 0;
+        self.last_flag = None;
+        self.safe_reveals = self.config.safe_reveals;
+        self.generation = None;
+        self.selection = None;
+        self.history = Some(History::new(seed));
+        self.events.clear();
+        self.place_mines();
+        self.calculate_neighbors();
+        self.open_start();
+    }
+
+    fn calculate_neighbors(&mut self) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !self.get(x, y).is_mine {
+                    let count = self.neighbors(x, y).filter(|&(nx, ny)| self.get(nx, ny).is_mine).count();
+                    self.get_mut(x, y).neighbor_mines = count as u8;
+                }
+            }
+        }
+    }
+    
+    /// Mines still unaccounted for, assuming every placed flag is on a mine:
+    /// `mines - flags - exploded`. It is negative when more flags than mines
+    /// have been placed and says nothing about whether the flags are actually
+    /// correct.
+    pub fn remaining_mines(&self) -> i64 {
+        self.mines as i64 - self.flags as i64 - self.exploded.len() as i64
+    }
+
+    /// Cells without a mine, revealed or not.
+    pub fn safe_cells(&self) -> usize {
+        self.width * self.height - self.mines
+    }
+
+    /// Safe cells that still have to be revealed to win; `revealed_safe`
+    /// is kept up to date as cells open, so this is never a recount.
+    pub fn safe_left(&self) -> usize {
+        self.safe_cells() - self.revealed_safe
+    }
+
+    /// The board's 3BV: the fewest reveals that clear it without chords or
+    /// flags. Each opening (a connected area of zeros, with the numbers on
+    /// its edge) takes one, and so does every number outside all openings.
+    /// It depends only on where the mines are, not on play.
+    pub fn calculate_3bv(&self) -> u32 {
+        let mut cleared = vec![false; self.width * self.height];
+        let mut clicks = 0;
+        for (x, y, cell) in self.iter_cells() {
+            if cell.is_mine || cell.neighbor_mines > 0 || cleared[y * self.width + x] {
+                continue;
+            }
+            clicks += 1;
+            cleared[y * self.width + x] = true;
+            let mut stack = vec![(x, y)];
+            while let Some((cx, cy)) = stack.pop() {
+                for (nx, ny) in self.neighbors(cx, cy) {
+                    let neighbor = self.get(nx, ny);
+                    if neighbor.is_mine || cleared[ny * self.width + nx] {
+                        continue;
+                    }
+                    cleared[ny * self.width + nx] = true;
+                    if neighbor.neighbor_mines == 0 {
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        let numbers = self.iter_cells().filter(|&(x, y, cell)| !cell.is_mine && !cleared[y * self.width + x]).count();
+        clicks + numbers as u32
+    }
+
+    /// Columns per cell: room for the widest number on the board plus a
+    /// separating space, which `compact` rendering leaves out.
+    pub fn cell_width(&self, compact: bool) -> u16 {
+        let widest = self.cells.iter().map(|c| c.neighbor_mines).max().unwrap_or(0);
+        widest.to_string().len() as u16 + if compact { 0 } else { 1 }
+    }
+
+    fn elapsed(&self) -> Duration {
+        match (self.start_time, self.end_time) {
+            (None, _) => Duration::ZERO,
+            (Some(start), None) => self.paused_at.unwrap_or_else(Instant::now).duration_since(start),
+            (Some(start), Some(end)) => end.duration_since(start),
+        }
+    }
+
+    /// The cell at `(x, y)`, which must be on the board.
+    pub fn get(&self, x: usize, y: usize) -> &Cell {
+        debug_assert!(x < self.width && y < self.height);
+        &self.cells[y * self.width + x]
+    }
+
+    /// `get`, for changing the cell.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> &mut Cell {
+        debug_assert!(x < self.width && y < self.height);
+        &mut self.cells[y * self.width + x]
+    }
+
+    /// The board one row at a time, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        // `chunks` refuses a width of 0, which only an empty board has.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Every cell with its `(x, y)` position, row by row.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (usize, usize, &Cell)> {
+        let width = self.width;
+        self.cells.iter().enumerate().map(move |(i, cell)| (i % width, i / width, cell))
+    }
+
+    /// The nearest hidden, unflagged cell next to a revealed number, by
+    /// Manhattan distance from `from`. Ties go to the first cell after `from`
+    /// in reading order, wrapping past the end; `from` itself is only
+    /// returned when it is the last one.
+    pub fn next_frontier_cell(&self, from: (usize, usize)) -> Option<(usize, usize)> {
+        let total = self.width * self.height;
+        let start = from.1 * self.width + from.0;
+        self.iter_cells()
+            .filter(|&(x, y, cell)| {
+                cell.covered()
+                    && self.neighbors(x, y).any(|(nx, ny)| {
+                        let n = self.get(nx, ny);
+                        n.state == CellState::Revealed && !n.is_mine && n.neighbor_mines > 0
+                    })
+            })
+            .map(|(x, y, _)| (x, y))
+            .min_by_key(|&(x, y)| {
+                let distance = x.abs_diff(from.0) + y.abs_diff(from.1);
+                let after = (y * self.width + x + total - start - 1) % total;
+                ((x, y) == from, distance, after)
+            })
+    }
+
+    /// The glyph for `cell`, independent of any frontend: `■` hidden, `⚑`
+    /// flagged, `?` questioned, `*` an open mine, a space for zero, or the
+    /// neighbor count.
+    /// Counts above 9 don't fit in one character and show as `+`, here and
+    /// in `Display` and `to_ascii`; that is a limit of the one-character
+    /// form only. A frontend with room draws `neighbor_mines` itself, as the
+    /// terminal UI does.
+    pub fn render_char(&self, cell: &Cell) -> char {
+        match cell.state {
+            CellState::Hidden => '■',
+            CellState::Flagged => '⚑',
+            CellState::Questioned => '?',
+            CellState::Revealed if cell.is_mine => '*',
+            CellState::Revealed if cell.neighbor_mines == 0 => ' ',
+            CellState::Revealed => char::from_digit(cell.neighbor_mines as u32, 10).unwrap_or('+'),
+        }
+    }
+
+    /// The solution as text, whatever has been revealed: one line per row
+    /// and one character per cell, `*` for a mine and the neighbor count
+    /// otherwise, `0` included. For `--dump` and bug reports; unlike the
+    /// `{:#}` form it has no status line and no blanks, so it can be compared
+    /// and parsed as it is.
+    pub fn to_ascii(&self) -> String {
+        let mut grid = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            for cell in row {
+                grid.push(if cell.is_mine {
+                    '*'
+                } else {
+                    char::from_digit(cell.neighbor_mines as u32, 10).unwrap_or('+')
+                });
+            }
+            grid.push('\n');
+        }
+        grid
+    }
+
+    /// Stops the clock of a game in progress until `resume`.
+    pub fn pause(&mut self) {
+        if self.start_time.is_some() && self.end_time.is_none() && self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// Starts a paused clock again, moving the start forward by the pause so
+    /// it doesn't count.
+    pub fn resume(&mut self) {
+        if let (Some(at), Some(start)) = (self.paused_at.take(), self.start_time) {
+            self.start_time = Some(start + at.elapsed());
+        }
+    }
+
+    /// Time for a finished game in seconds, rounded up to hundredths.
+    pub fn final_time(&self) -> f64 {
+        rounded_time(self.elapsed())
+    }
+
+    /// Ends an unfinished game as lost, leaving its cells as they are. Used
+    /// on the other boards of a `--split` game once one of them is lost.
+    pub fn abandon(&mut self) {
+        if !self.game_over && !self.victory {
+            self.game_over = true;
+            self.end_time = Some(Instant::now());
+            self.pending.clear();
+        }
+    }
+
+    /// Places mines together with their mirror image under the configured
+    /// symmetry. Cells that are their own mirror take a single mine, which is
+    /// how an odd count is reached; if no such cell is left, the last mine is
+    /// placed unpaired so the total is always exactly `mines`.
+    fn place_mines(&mut self) {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let symmetry = self.config.symmetry;
+        let mut placed = 0;
+        while placed < self.mines {
+            let (x, y) = self.pick_cell(&mut rng);
+            let (mx, my) = symmetry.mirror(x, y, self.width, self.height);
+            if self.get(x, y).is_mine || self.kept_clear(x, y) || self.kept_clear(mx, my) {
+                continue;
+            }
+            if (mx, my) != (x, y) {
+                if self.mines - placed == 1 && self.has_free_self_mirror() {
+                    continue;
+                }
+                if self.mines - placed >= 2 {
+                    self.get_mut(mx, my).is_mine = true;
+                    placed += 1;
+                }
+            }
+            self.get_mut(x, y).is_mine = true;
+            placed += 1;
+        }
+    }
+
+    /// Draws a cell for the next mine. Uniform by default, so seeded boards
+    /// stay as they were; with `clustering` set, each free cell is weighted
+    /// by `e^(clustering × mines around it)`.
+    fn pick_cell(&self, rng: &mut StdRng) -> (usize, usize) {
+        let clustering = self.config.clustering;
+        if clustering == 0.0 {
+            return (rng.random_range(0..self.width), rng.random_range(0..self.height));
+        }
+        let weighted: Vec<((usize, usize), f64)> = self
+            .iter_cells()
+            .filter(|&(x, y, cell)| !cell.is_mine && !self.kept_clear(x, y))
+            .map(|(x, y, _)| {
+                let mines = self.neighbors(x, y).filter(|&(nx, ny)| self.get(nx, ny).is_mine).count();
+                ((x, y), (clustering * mines as f64).exp())
+            })
+            .collect();
+        let mut target = rng.random::<f64>() * weighted.iter().map(|(_, weight)| weight).sum::<f64>();
+        for &(cell, weight) in &weighted {
+            if target < weight {
+                return cell;
+            }
+            target -= weight;
+        }
+        // Only reachable through rounding in the sum.
+        weighted.last().map_or((0, 0), |&(cell, _)| cell)
+    }
+
+    /// Whether some mine-free cell maps onto itself under the symmetry.
+    fn has_free_self_mirror(&self) -> bool {
+        let symmetry = self.config.symmetry;
+        (0..self.height).any(|y| {
+            (0..self.width).any(|x| {
+                !self.get(x, y).is_mine
+                    && !self.kept_clear(x, y)
+                    && symmetry.mirror(x, y, self.width, self.height) == (x, y)
+            })
+        })
+    }
+
+    /// Whether `(x, y)` is on the edge `safe_edge` keeps free of mines.
+    fn kept_clear(&self, x: usize, y: usize) -> bool {
+        self.config.safe_edge && (x == 0 || y == 0)
+    }
+
+    /// Moves every mine on `(x, y)` and its neighbors (the 3x3 on a square
+    /// board) to a free cell outside them, starting with `(x, y)` itself. On
+    /// a board too dense to take them all, the remaining mines stay put.
+    fn clear_opening(&mut self, x: usize, y: usize) {
+        let mut opening = vec![(x, y)];
+        opening.extend(self.neighbors(x, y));
+        self.clear_cells(opening);
+    }
+
+    /// Moves the mines on `cells` to free cells elsewhere, in order, for as
+    /// long as there are free cells.
+    fn clear_cells(&mut self, cells: Vec<(usize, usize)>) {
+        let mut free: Vec<(usize, usize)> = (0..self.height)
+            .flat_map(|cy| (0..self.width).map(move |cx| (cx, cy)))
+            .filter(|&(cx, cy)| !cells.contains(&(cx, cy)) && !self.get(cx, cy).is_mine && !self.kept_clear(cx, cy))
+            .collect();
+        // Derived from the board seed so a seeded board relocates identically.
+        free.shuffle(&mut StdRng::seed_from_u64(self.seed.wrapping_add(1)));
+
+        for (cx, cy) in cells {
+            if !self.get(cx, cy).is_mine {
+                continue;
+            }
+            let Some(to) = free.pop() else { break };
+            self.move_mine((cx, cy), to);
+        }
+    }
+
+    /// A reveal that can't lose while safe reveals last: a mine on `(x, y)`
+    /// is first moved to a hidden cell, one away from every number on show
+    /// when there is one, so as few numbers as possible change under the
+    /// player. Once they are spent, or on a cell that isn't hidden, it is a
+    /// plain reveal that costs nothing.
+    fn safe_reveal(&mut self, x: usize, y: usize) {
+        if self.safe_reveals > 0 && self.get(x, y).covered() {
+            self.safe_reveals -= 1;
+            if self.get(x, y).is_mine {
+                let mut free: Vec<(usize, usize)> = self
+                    .iter_cells()
+                    .filter(|&(cx, cy, cell)| {
+                        (cx, cy) != (x, y) && cell.covered() && !cell.is_mine && !self.kept_clear(cx, cy)
+                    })
+                    .map(|(cx, cy, _)| (cx, cy))
+                    .collect();
+                // Derived from the seed and the progress so far, so a
+                // replayed history moves the same mine to the same cell.
+                let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.revealed_safe as u64 + 2));
+                free.shuffle(&mut rng);
+                let unseen = free.iter().copied().find(|&(cx, cy)| {
+                    self.neighbors(cx, cy).all(|(nx, ny)| self.get(nx, ny).state != CellState::Revealed)
+                });
+                if let Some(to) = unseen.or(free.first().copied()) {
+                    self.move_mine((x, y), to);
+                }
+            }
+        }
+        self.reveal(x, y);
+    }
+
+    /// Moves the mine at `from` to the mine-free cell `to`, updating only the
+    /// counts around the two cells instead of rerunning
+    /// `calculate_neighbors`. Anything else is left alone.
+    fn move_mine(&mut self, from: (usize, usize), to: (usize, usize)) {
+        if from == to || !self.get(from.0, from.1).is_mine || self.get(to.0, to.1).is_mine {
+            return;
+        }
+        self.get_mut(from.0, from.1).is_mine = false;
+        let mut count = 0;
+        for (nx, ny) in self.neighbors_copied(from.0, from.1) {
+            let neighbor = self.get_mut(nx, ny);
+            if neighbor.is_mine {
+                count += 1;
+            } else {
+                neighbor.neighbor_mines -= 1;
+            }
+        }
+        self.get_mut(from.0, from.1).neighbor_mines = count;
+
+        self.get_mut(to.0, to.1).is_mine = true;
+        for (nx, ny) in self.neighbors_copied(to.0, to.1) {
+            let neighbor = self.get_mut(nx, ny);
+            if !neighbor.is_mine {
+                neighbor.neighbor_mines += 1;
+            }
+        }
+    }
+
+    /// The one place the clock starts. Every action that opens cells calls
+    /// this first; only the first call, at `(x, y)`, does anything. The
+    /// board itself is only rearranged while nothing is open yet, which
+    /// matters after an `AutoFirst::Waiting` opening.
+    fn begin(&mut self, x: usize, y: usize) {
+        if self.start_time.is_some() {
+            return;
+        }
+        if self.revealed_safe == 0 {
+            if self.config.big_opening {
+                self.clear_opening(x, y);
+            }
+            if self.config.no_guess {
+                self.make_solvable(x, y);
+            }
+            // The first reveal never loses, even on the board `--no-guess`
+            // settled for after running out of attempts. With fewer mines
+            // than cells there is always a free cell to move its mine to,
+            // however dense the board.
+            self.clear_cells(vec![(x, y)]);
+        }
+        self.start_time = Some(Instant::now());
+    }
+
+    /// Under `AutoFirst`, reveals a starting cell on a new board, cascade
+    /// and all. With `--big-opening` or `--no-guess` that is the center,
+    /// which they then make safe; otherwise it is the empty cell nearest the
+    /// center, or failing that the safe cell with the fewest mines around.
+    /// A `FirstCell` overrides both the choice and `AutoFirst` itself: that
+    /// cell is always opened and the clock starts with it.
+    fn open_start(&mut self) {
+        if self.config.auto_first == AutoFirst::Off && self.config.first_cell.is_none() {
+            return;
+        }
+        let center = (self.width / 2, self.height / 2);
+        let start = if let Some(cell) = self.config.first_cell {
+            Some(cell.position(self.width, self.height))
+        } else if self.config.big_opening || self.config.no_guess {
+            Some(center)
+        } else {
+            self.iter_cells()
+                .filter(|(_, _, cell)| !cell.is_mine)
+                .min_by_key(|&(x, y, cell)| (cell.neighbor_mines, x.abs_diff(center.0) + y.abs_diff(center.1)))
+                .map(|(x, y, _)| (x, y))
+        };
+        let Some((x, y)) = start else { return };
+        self.reveal(x, y);
+        self.process_pending(usize::MAX);
+        let waiting = self.config.auto_first == AutoFirst::Waiting && self.config.first_cell.is_none();
+        if waiting && !self.game_over && !self.victory {
+            self.start_time = None;
+        }
+    }
+
+    /// Opens `(x, y)` as the player. The cascade from an empty cell is not
+    /// recursive: cells wait in `pending` and `process_pending` works through
+    /// them, so even a huge board opens in constant stack space, and victory
+    /// is checked once the queue is empty rather than per cell. A finished
+    /// game is left as it is.
+    pub fn reveal(&mut self, x: usize, y: usize) {
+        if self.game_over || self.victory {
+            return;
+        }
+        self.begin(x, y);
+        if !self.get(x, y).covered() {
+            return;
+        }
+
+        // Gentle mode opens the whole 3x3, clicked cell first, skipping
+        // flags; the first mine among them ends the sweep.
+        let mut targets = vec![(x, y)];
+        if self.config.gentle {
+            targets.extend(self.neighbors(x, y));
+        }
+        for (cx, cy) in targets {
+            if !self.get(cx, cy).covered() {
+                continue;
+            }
+            if self.get(cx, cy).is_mine {
+                self.trigger_mine(cx, cy);
+                return;
+            }
+            self.open_cell(cx, cy);
+        }
+        self.process_pending(self.config.reveal_budget);
+    }
+
+    /// The player stepped on the mine at `(x, y)`. The mine is left open,
+    /// shown as a red `*`, and the configured `MineTrigger` decides whether
+    /// the game goes on.
+    fn trigger_mine(&mut self, x: usize, y: usize) {
+        self.emit(GameEvent::MineHit(x, y));
+        if self.config.mine_trigger == MineTrigger::Zen {
+            self.get_mut(x, y).state = CellState::Flagged;
+            self.flags += 1;
+            self.emit(GameEvent::Flagged(x, y, true));
+            return;
+        }
+        self.get_mut(x, y).state = CellState::Revealed;
+        self.exploded.push((x, y));
+        self.lives = self.lives.saturating_sub(1);
+        let forgiven = match self.config.mine_trigger {
+            MineTrigger::Lose => false,
+            MineTrigger::Forgiving { .. } => self.lives > 0,
+            MineTrigger::Practice | MineTrigger::Zen => true,
+        };
+        if !forgiven {
+            self.game_over = true;
+            self.end_time = Some(Instant::now());
+            self.pending.clear();
+            self.emit(GameEvent::GameOver);
+        }
+    }
+
+    /// Reveals one safe hidden cell and, if it has no neighboring mines,
+    /// queues it so the flood fill expands from it. Cells are marked revealed
+    /// as they are queued, which keeps any cell from being queued twice.
+    fn open_cell(&mut self, x: usize, y: usize) {
+        self.get_mut(x, y).state = CellState::Revealed;
+        self.revealed_safe += 1;
+        self.emit(GameEvent::Revealed(x, y));
+        if self.get(x, y).neighbor_mines == 0 {
+            self.pending.push_back((x, y));
+        }
+    }
+
+    /// `neighbors` copied onto the stack, for loops that change the board as
+    /// they go: nothing is allocated and `self` isn't held borrowed.
+    fn neighbors_copied(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        let mut cells = [(0, 0); 8];
+        let mut len = 0;
+        for cell in self.neighbors(x, y) {
+            cells[len] = cell;
+            len += 1;
+        }
+        cells.into_iter().take(len)
+    }
+
+    /// Expands at most `budget` queued empty cells, so that a huge cascade
+    /// is spread over several frames instead of stalling one.
+    pub fn process_pending(&mut self, budget: usize) {
+        for _ in 0..budget {
+            let Some((x, y)) = self.pending.pop_front() else { break };
+            for (nx, ny) in self.neighbors_copied(x, y) {
+                if self.get(nx, ny).covered() {
+                    self.open_cell(nx, ny);
+                }
+            }
+        }
+        if self.pending.is_empty() {
+            self.check_victory();
+        }
+    }
+
+    /// Opens every hidden neighbor of a revealed number once the number of
+    /// adjacent flags matches it. A wrong flag makes this hit a mine.
+    pub fn chord(&mut self, x: usize, y: usize) {
+        let cell = *self.get(x, y);
+        if cell.state != CellState::Revealed || cell.neighbor_mines == 0 {
+            return;
+        }
+        self.begin(x, y);
+        let flagged = self.neighbors(x, y).filter(|&(nx, ny)| self.get(nx, ny).marked_mine()).count();
+        if flagged != cell.neighbor_mines as usize {
+            return;
+        }
+        for (nx, ny) in self.neighbors_copied(x, y) {
+            if self.game_over {
+                break;
+            }
+            self.reveal(nx, ny);
+        }
+    }
+
+    /// Cycles a hidden cell through flagged and `?` back to hidden. Only the
+    /// flag counts towards `flags`.
+    pub fn toggle_flag(&mut self, x: usize, y: usize) {
+        match self.get(x, y).state {
+            CellState::Hidden if self.config.strict_flag_limit && self.flags >= self.mines => {
+                self.flag_limit_hit = Some(Instant::now());
+            }
+            CellState::Hidden => {
+                self.get_mut(x, y).state = CellState::Flagged;
+                self.flags += 1;
+                self.emit(GameEvent::Flagged(x, y, true));
+            }
+            CellState::Flagged => {
+                self.get_mut(x, y).state = CellState::Questioned;
+                self.flags -= 1;
+                self.emit(GameEvent::Flagged(x, y, false));
+            }
+            CellState::Questioned => self.get_mut(x, y).state = CellState::Hidden,
+            CellState::Revealed => return,
+        }
+        if self.config.win_condition == WinCondition::FlagMines {
+            self.check_victory();
+        }
+    }
+
+    /// Puts the cell at `(x, y)` back the way it was before the last move,
+    /// if that move flagged or unflagged it. Only one step is kept, and
+    /// nothing else can be taken back.
+    pub fn undo_flag(&mut self, x: usize, y: usize) {
+        let Some((fx, fy, before)) = self.last_flag.take() else { return };
+        if (fx, fy) != (x, y) || self.get(x, y).state == CellState::Revealed {
+            return;
+        }
+        let now = mem::replace(&mut self.get_mut(x, y).state, before);
+        match (now, before) {
+            (CellState::Flagged, _) => {
+                self.flags -= 1;
+                self.emit(GameEvent::Flagged(x, y, false));
+            }
+            (_, CellState::Flagged) => {
+                self.flags += 1;
+                self.emit(GameEvent::Flagged(x, y, true));
+            }
+            _ => {}
+        }
+        if self.config.win_condition == WinCondition::FlagMines {
+            self.check_victory();
+        }
+    }
+
+    /// The hidden cells in the rectangle spanned by corners `a` and `b`,
+    /// which flagging a selection flags one by one through `toggle_flag`, so
+    /// the strict limit still holds and flagged or revealed cells are left as
+    /// they are.
+    pub fn hidden_in_rect(&self, a: (usize, usize), b: (usize, usize)) -> Vec<(usize, usize)> {
+        (a.1.min(b.1)..=a.1.max(b.1))
+            .flat_map(|y| (a.0.min(b.0)..=a.0.max(b.0)).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(x, y).state == CellState::Hidden)
+            .collect()
+    }
+
+    /// A `MineTrigger::Practice` board cleared after stepping on mines.
+    pub fn practice_completion(&self) -> bool {
+        self.victory && self.config.mine_trigger == MineTrigger::Practice && !self.exploded.is_empty()
+    }
+
+    /// Whether every cell still unrevealed is a mine, i.e. nothing is left
+    /// to find but the mines themselves.
+    fn only_mines_hidden(&self) -> bool {
+        self.iter_cells().all(|(_, _, cell)| cell.state == CellState::Revealed || cell.is_mine)
+    }
+
+    /// Finishes a board with only mines left unrevealed: flags each of them
+    /// and declares the win without waiting for a cascade still in progress.
+    /// Also tidies up a board that was already won. Does nothing, and
+    /// returns false, while any safe cell remains to be found.
+    pub fn auto_complete(&mut self) -> bool {
+        if self.game_over || self.start_time.is_none() || !self.only_mines_hidden() {
+            return false;
+        }
+        self.process_pending(usize::MAX);
+        self.flag_covered();
+        self.check_victory();
+        event_log::event(format_args!("auto-complete"));
+        true
+    }
+
+    /// Flags every cell not yet opened or flagged; on a won board those are
+    /// exactly the mines.
+    fn flag_covered(&mut self) {
+        let hidden: Vec<_> = self
+            .iter_cells()
+            .filter(|(_, _, cell)| cell.covered())
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        for (x, y) in hidden {
+            self.get_mut(x, y).state = CellState::Flagged;
+            self.flags += 1;
+            self.emit(GameEvent::Flagged(x, y, true));
+        }
+    }
+
+    /// The hint key: opens the cell pointed out last time if it is still
+    /// covered, or else points the cursor at a new provably safe cell and
+    /// counts the hint. False when no cell can be proven safe.
+    pub fn ask_hint(&mut self) -> bool {
+        if let Some((x, y)) = self.hint.take().filter(|&(x, y)| self.get(x, y).covered()) {
+            Move::Reveal(x, y).apply(self);
+            return true;
+        }
+        let Some((x, y)) = self.find_safe_cell() else { return false };
+        self.hint = Some((x, y));
+        self.hints_used += 1;
+        self.cursor_x = x;
+        self.cursor_y = y;
+        true
+    }
+
+    /// One pass of the simplest rule: a number with exactly as many unopened
+    /// neighbors as mines it still misses has all of them as mines. Those
+    /// still plainly hidden are flagged as moves of the player's own, `?`
+    /// cells are left alone, and no more flags go down than there are mines.
+    /// The player's flags count for nothing here, right or wrong.
+    /// Returns how many flags were placed; none on a board with nothing forced.
+    pub fn auto_flag(&mut self) -> usize {
+        let before = self.flags;
+        for deduction in self.obvious_deductions() {
+            let Deduction::Mine(x, y) = deduction else { continue };
+            if self.flags >= self.mines {
+                break;
+            }
+            if self.get(x, y).state == CellState::Hidden {
+                Move::Flag(x, y).apply(self);
+            }
+        }
+        self.flags - before
+    }
+
+    /// Whether `(x, y)` lies inside the current selection.
+    pub fn selected(&self, x: usize, y: usize) -> bool {
+        self.selection.is_some_and(|(ax, ay)| {
+            (ax.min(self.cursor_x)..=ax.max(self.cursor_x)).contains(&x)
+                && (ay.min(self.cursor_y)..=ay.max(self.cursor_y)).contains(&y)
+        })
+    }
+
+    /// Ends the game as won once the `WinCondition` holds. Only the
+    /// transition stamps `end_time`, so calling this again is harmless.
+    fn check_victory(&mut self) {
+        if self.victory || self.game_over {
+            return;
+        }
+        let flagged_exactly = self.config.win_condition == WinCondition::FlagMines
+            && self.iter_cells().all(|(_, _, cell)| cell.marked_mine() == cell.is_mine);
+        if self.safe_left() == 0 || flagged_exactly {
+            self.victory = true;
+            self.end_time = Some(Instant::now());
+            // After the clock stops, so the flags can't add to the time.
+            if self.config.auto_complete && self.safe_left() == 0 {
+                self.flag_covered();
+            }
+            self.emit(GameEvent::Victory);
+        }
+    }
+}
+
+/// The board as text, one row per line in `render_char` glyphs, followed by
+/// a status line. The alternate form (`{:#}`) draws every cell as if it had
+/// been revealed, for looking at a layout while debugging.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            let line: String = row
+                .iter()
+                .map(|&cell| {
+                    let shown = if f.alternate() { Cell { state: CellState::Revealed, ..cell } } else { cell };
+                    self.render_char(&shown)
+                })
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        let outcome = if self.victory {
+            "胜利"
+        } else if self.game_over {
+            "失败"
+        } else {
+            "进行中"
+        };
+        write!(
+            f,
+            "{}x{}  旗帜 {}/{}  剩余安全格 {}  {}",
+            self.width,
+            self.height,
+            self.flags,
+            self.mines,
+            self.safe_left(),
+            outcome
+        )
+    }
+}
+
+/// Large enough that ordinary boards finish their cascade within one frame.
+pub const DEFAULT_REVEAL_BUDGET: usize = 10_000;
+/// Far beyond any board a terminal can show, but small enough that a typo
+/// in a board code or preset can't run the machine out of memory.
+pub const DEFAULT_MAX_CELLS: usize = 1_000_000;
+
+/// Rounds a finished game's time up to hundredths of a second. A one-click
+/// win can end within the same instant it started, so the result is never
+/// below 0.01.
+pub fn rounded_time(elapsed: Duration) -> f64 {
+    ((elapsed.as_secs_f64() * 100.0).ceil() / 100.0).max(0.01)
+}
+
+/// At least one cell has to be safe, or the first reveal always loses and
+/// random placement never finds a free cell for the last mine.
+fn check_mines(width: usize, height: usize, mines: usize) -> Result<(), String> {
+    if mines > 0 && mines >= width * height {
+        return Err(format!("{}x{} 的棋盘最多只能有 {} 个地雷", width, height, (width * height).saturating_sub(1)));
+    }
+    Ok(())
+}
+
+/// Largest `--clustering` factor either way. Past this every mine already
+/// lands next to another (or away from all others), so more changes nothing.
+pub const MAX_CLUSTERING: f64 = 5.0;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moving_mines_keeps_the_counts_a_full_recount_gives() {
+        let mut game = Game::from_config(GameConfig::default().size(16, 16).mines(40).seed(7)).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let mines: Vec<_> = game.iter_cells().filter(|(_, _, c)| c.is_mine).map(|(x, y, _)| (x, y)).collect();
+            let free: Vec<_> = game.iter_cells().filter(|(_, _, c)| !c.is_mine).map(|(x, y, _)| (x, y)).collect();
+            let (from, to) = (mines[rng.random_range(0..mines.len())], free[rng.random_range(0..free.len())]);
+            game.move_mine(from, to);
+        }
+        assert_eq!(game.cells.iter().filter(|c| c.is_mine).count(), 40);
+        let mut recounted = game.clone();
+        recounted.calculate_neighbors();
+        let counts = |game: &Game| game.cells.iter().map(|c| c.neighbor_mines).collect::<Vec<_>>();
+        assert_eq!(counts(&game), counts(&recounted));
+    }
+
+    #[test]
+    fn a_flood_fill_queues_and_opens_each_cell_once() {
+        // Every cell of an empty board is a blank, the most the queue can be
+        // asked to take. Expanding one cell at a time shows what each queues.
+        let (width, height) = (200, 150);
+        let config = GameConfig::default().size(width, height).mines(0).reveal_budget(0);
+        let mut game = Game::from_config(config).unwrap();
+        game.reveal(100, 75);
+        let mut queued = vec![0; width * height];
+        queued[75 * width + 100] = 1;
+        let mut expansions = 0;
+        while !game.pending.is_empty() {
+            let kept = game.pending.len() - 1;
+            game.process_pending(1);
+            expansions += 1;
+            for &(x, y) in game.pending.iter().skip(kept) {
+                queued[y * width + x] += 1;
+            }
+        }
+        assert!(queued.iter().all(|&times| times == 1));
+        assert_eq!(expansions, width * height);
+        let mut opened = vec![0; width * height];
+        for event in game.drain_events() {
+            if let GameEvent::Revealed(x, y) = event {
+                opened[y * width + x] += 1;
+            }
+        }
+        assert!(opened.iter().all(|&times| times == 1));
+        assert!(game.victory);
+    }
+
+    /// Boards under `symmetry` mirror every mine, have exactly the mines
+    /// asked for and count them as a recount does. The odd board has a
+    /// center cell (a center column or row for the mirrors) and an odd count
+    /// that has to use it; the even board has neither.
+    fn check_symmetry(symmetry: Symmetry) {
+        for (width, height, mines) in [(9, 7, 11), (10, 8, 20)] {
+            for seed in 0..20 {
+                let config = GameConfig::default().size(width, height).mines(mines).seed(seed).symmetry(symmetry);
+                let game = Game::from_config(config).unwrap();
+                for (x, y, cell) in game.iter_cells() {
+                    let (mx, my) = symmetry.mirror(x, y, width, height);
+                    assert_eq!(cell.is_mine, game.get(mx, my).is_mine, "{:?} seed {}: ({}, {})", symmetry, seed, x, y);
+                }
+                assert_eq!(game.cells.iter().filter(|c| c.is_mine).count(), mines);
+                let mut recounted = game.clone();
+                recounted.calculate_neighbors();
+                let counts = |game: &Game| game.cells.iter().map(|c| c.neighbor_mines).collect::<Vec<_>>();
+                assert_eq!(counts(&game), counts(&recounted));
+            }
+        }
+    }
+
+    #[test]
+    fn unmirrored_boards_have_their_mines_and_counts() {
+        check_symmetry(Symmetry::None);
+    }
+
+    #[test]
+    fn horizontal_symmetry_mirrors_left_and_right() {
+        check_symmetry(Symmetry::Horizontal);
+    }
+
+    #[test]
+    fn vertical_symmetry_mirrors_top_and_bottom() {
+        check_symmetry(Symmetry::Vertical);
+    }
+
+    #[test]
+    fn rotational_symmetry_puts_the_odd_mine_in_the_center() {
+        check_symmetry(Symmetry::Rotational);
+        for seed in 0..20 {
+            let config = GameConfig::default().size(9, 7).mines(11).seed(seed).symmetry(Symmetry::Rotational);
+            assert!(Game::from_config(config).unwrap().get(4, 3).is_mine);
+        }
+    }
+}
@@ -1,207 +1,3114 @@
-use std::{io, time::Instant};
-use crossterm::{
-    cursor::{Hide, Show},
-    event::{self, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use rand::Rng;
-use tui::{
-    backend::CrosstermBackend,
-    style::{Color, Style},
-    text::Span,
-    widgets::Paragraph,
-    Terminal,
-};
-
-#[derive(Clone, Copy, PartialEq)]
-enum CellState {
-    Hidden,
-    Revealed,
-    Flagged,
-}
-
-#[derive(Clone, Copy, PartialEq)]
-struct Cell {
-    is_mine: bool,
-    state: CellState,
-    neighbor_mines: u8,
-}
-
-struct Game {
-    width: usize,
-    height: usize,
-    mines: usize,
-    cells: Vec<Vec<Cell>>,
-    cursor_x: usize,
-    cursor_y: usize,
-    game_over: bool,
-    victory: bool,
-    start_time: Option<Instant>,
-    end_time: Option<Instant>,
-    flags: usize,
-}
-
-impl Game {
-    fn new(width: usize, height: usize, mines: usize) -> Self {
-        let mut game = Game {
-            width,
-            height,
-            mines,
-            cells: vec![vec![Cell { is_mine: false, state: CellState::Hidden, neighbor_mines: 0 }; width]; height],
-            cursor_x: 0,
-            cursor_y: 0,
-            game_over: false,
-            victory: false,
-            start_time: None,
-            end_time: None,
-            flags: 0,
-        };
-        game.place_mines();
-        game.calculate_neighbors();
-        game
-    }
-
-    fn calculate_neighbors(&mut self) {
-        for y in 0..self.height {
-            for x in 0..self.width {
-                if !self.cells[y][x].is_mine {
-                    let mut count = 0;
-                    for dy in -1..=1 {
-                        for dx in -1..=1 {
-                            if dy == 0 && dx == 0 { continue; }
-                            let ny = y as i32 + dy;
-                            let nx = x as i32 + dx;
-                            if ny >= 0 && ny < self.height as i32 && nx >= 0 && nx < self.width as i32 {
-                                if self.cells[ny as usize][nx as usize].is_mine {
-                                    count += 1;
-                                }
-                            }
-                        }
-                    }
-                    self.cells[y][x].neighbor_mines = count;
-                }
-            }
-        }
-    }
-    
-    fn get_elapsed_time(&self) -> u64 {
-        match (self.start_time, self.end_time) {
-            (None, _) => 0,
-            (Some(start), None) => start.elapsed().as_secs(),
-            (Some(start), Some(end)) => end.duration_since(start).as_secs(),
-        }
-    }
-
-    fn place_mines(&mut self) {
-        let mut rng = rand::rng();
-        let mut placed = 0;
-        while placed < self.mines {
-            let x = rng.random_range(0..self.width);
-            let y = rng.random_range(0..self.height);
-            if !self.cells[y][x].is_mine {
-                self.cells[y][x].is_mine = true;
-                placed += 1;
-            }
-        }
-    }
-
-    fn reveal(&mut self, x: usize, y: usize) {
-        if self.start_time.is_none() {
-            self.start_time = Some(Instant::now());
-        }
-        if self.cells[y][x].state != CellState::Hidden {
-            return;
-        }
-
-        if self.cells[y][x].is_mine {
-            self.game_over = true;
-            self.end_time = Some(Instant::now());
-            return;
-        }
-
-        self.cells[y][x].state = CellState::Revealed;
-        self.check_victory();
-    }
-
-    fn check_victory(&mut self) {
-        let mut revealed_count = 0;
-        for row in &self.cells {
-            for cell in row {
-                if cell.state == CellState::Revealed && !cell.is_mine {
-                    revealed_count += 1;
-                }
-            }
-        }
-        let total_safe = self.width * self.height - self.mines;
-        self.victory = revealed_count == total_safe;
-        if self.victory {
-            self.end_time = Some(Instant::now());
-        }
-    }
-}
-
-struct Leaderboard {
-    records: [Option<u64>; 3],
-}
-
-impl Leaderboard {
-    fn new() -> Self {
-        Self { records: [None, None, None] }
-    }
-
-    fn update(&mut self, difficulty: usize, time: u64) {
-        if let Some(best_time) = self.records[difficulty] {
-            if time < best_time {
-                self.records[difficulty] = Some(time);
-            }
-        } else {
-            self.records[difficulty] = Some(time);
-        }
-    }
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let difficulties = [(8, 8, 10), (16, 16, 40), (24, 20, 99)];
-    
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, Hide)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    terminal.clear()?;
-
-    let mut leaderboard = Leaderboard::new();
-    let difficulty = 0;
-    let mut game = Game::new(difficulties[difficulty].0, difficulties[difficulty].1, difficulties[difficulty].2);
-
-    loop {
-        terminal.draw(|f| {
-            let elapsed_time_text = format!("时间: {} 秒", game.get_elapsed_time());
-            let elapsed_paragraph = Paragraph::new(Span::styled(
-                elapsed_time_text, Style::default().fg(Color::Cyan)
-            ));
-            f.render_widget(elapsed_paragraph, f.size());
-        })?;
-
-        if let Event::Key(key) = event::read()? {
-            match key.code {
-                KeyCode::Char('q') => break,
-                KeyCode::Char('r') => {
-                    game = Game::new(difficulties[difficulty].0, difficulties[difficulty].1, difficulties[difficulty].2);
-                }
-                KeyCode::Char(' ') => {
-                    game.reveal(game.cursor_x, game.cursor_y);
-                    if game.victory {
-                        leaderboard.update(difficulty, game.get_elapsed_time());
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show)?;
-    Ok(())
-}
+mod config;
+mod export;
+mod keys;
+mod menu;
+mod settings;
+mod sound;
+mod theme;
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    env, fs,
+    io::{self, IsTerminal, Write},
+    mem, panic,
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{
+        self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen, LeaveAlternateScreen,
+        SetTitle,
+    },
+};
+use minesweeper::{
+    adaptive::Adaptive,
+    code::BoardCode,
+    event_log,
+    game_event::GameEvent,
+    history::{Recording, Review},
+    save::{Autosaver, SavedGame, StateDump},
+    scores::{daily_seed, utc_date_today, Entry, Scores, Tally, NAME_LENGTH},
+    strategy::{self, Move, Strategy},
+    topology::Topology,
+    AutoFirst, Cell, CellState, FirstCell, Game, GameConfig, MineTrigger, Symmetry, WinCondition, DEFAULT_MAX_CELLS,
+    DEFAULT_REVEAL_BUDGET, MAX_CLUSTERING, rounded_time,
+};
+use keys::KeyBindings;
+use menu::{select_difficulty, MenuChoice};
+use sound::{Sound, Tone};
+use theme::Theme;
+use config::{Config, Difficulty, EndScreen, Settings};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph, Wrap},
+    Frame, Terminal,
+};
+
+/// Columns of the progress bar beside the status bar, and the least width
+/// the two need together before the bar is shown.
+const PROGRESS_WIDTH: u16 = 18;
+const PROGRESS_MIN_WIDTH: u16 = 100;
+/// How long the flag counter flashes after a refused flag.
+const FLAG_LIMIT_FLASH: Duration = Duration::from_millis(300);
+/// How long the borders flash under `--bell` after a mine or a win, for
+/// terminals that keep the bell quiet.
+const BELL_FLASH: Duration = Duration::from_millis(150);
+/// How long the `--no-guess` generation report stays up without `--verbose`.
+const GENERATION_REPORT_DURATION: Duration = Duration::from_secs(3);
+/// How long the outcome of a one-off action stays in the status bar.
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+/// Delay between moves when a `--strategy` is playing, so the game stays watchable.
+const AUTOPLAY_INTERVAL: Duration = Duration::from_millis(100);
+/// Shortest time between two `--title` updates while a game is running.
+const TITLE_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a movement key is held before `--repeat-ms` starts repeating it.
+const REPEAT_DELAY: Duration = Duration::from_millis(250);
+
+/// The mine glyph under a theme with `shapes`, unlike any other symbol.
+const SHAPE_MINE: char = '@';
+
+/// The text drawn for `cell`: its glyph, or the full count when it has more
+/// than one digit.
+fn cell_symbol(game: &Game, cell: &Cell) -> String {
+    match cell.state {
+        CellState::Revealed if !cell.is_mine && cell.neighbor_mines > 9 => cell.neighbor_mines.to_string(),
+        CellState::Revealed if cell.is_mine && theme::current().shapes => SHAPE_MINE.to_string(),
+        _ => game.render_char(cell).to_string(),
+    }
+}
+
+fn cell_color(cell: &Cell) -> Color {
+    let theme = theme::current();
+    match cell.state {
+        CellState::Hidden => theme.hidden,
+        CellState::Flagged => theme.flag,
+        CellState::Questioned => theme.question,
+        CellState::Revealed if cell.is_mine => theme.mine,
+        CellState::Revealed => number_color(theme, cell.neighbor_mines),
+    }
+}
+
+/// Base color for a neighbor count in `theme`. The classic theme follows
+/// the classic game: bright and dark pairs for blue (1 and 4) and red (3
+/// and 5), then teal and gray. 7 is black there, which a dark terminal
+/// can't show, so it is white here. Counts past 8, only possible on
+/// unusual boards, share 8's color outside the classic theme.
+/// `theme::resolve` picks the shade that is actually drawn.
+fn number_color(theme: &Theme, count: u8) -> Color {
+    match count {
+        1..=8 => theme.numbers[count as usize - 1],
+        9.. if theme == &theme::THEMES[0] => Color::LightMagenta,
+        9.. => theme.numbers[7],
+        _ => theme.hidden,
+    }
+}
+
+/// Foreground used on top of the `DarkGray` cursor background. Dark colors
+/// are swapped for their light variants so every symbol stays readable.
+fn highlight_fg(color: Color) -> Color {
+    match color {
+        Color::Black | Color::DarkGray | Color::Gray => Color::White,
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        other => other,
+    }
+}
+
+fn cell_style(cell: &Cell, highlighted: bool) -> Style {
+    let color = cell_color(cell);
+    let theme = theme::current();
+    let style = if theme.shapes && cell.state == CellState::Flagged {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    // Reversed stays visible whatever colors the cell and terminal have.
+    if highlighted && theme.shapes {
+        style.fg(theme::resolve(color)).add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    } else if highlighted {
+        style
+            .fg(theme::resolve(theme.cursor_text.unwrap_or_else(|| highlight_fg(color))))
+            .bg(theme::resolve(theme.cursor))
+            .add_modifier(Modifier::BOLD)
+    } else {
+        style.fg(theme::resolve(color))
+    }
+}
+
+/// What the boards add up to: the one board normally, or the two of a
+/// `--split` game, which is lost with either board and won only with both.
+struct Round {
+    lost: bool,
+    won: bool,
+    /// From the first reveal on any board until the last one finished.
+    elapsed: Duration,
+}
+
+impl Round {
+    fn of(boards: &[Game]) -> Self {
+        let finished = boards.iter().all(|board| board.game_over || board.victory);
+        let start = boards.iter().filter_map(|board| board.start_time).min();
+        let end = if finished {
+            boards.iter().filter_map(|board| board.end_time).max()
+        } else {
+            boards.iter().find_map(|board| board.paused_at)
+        };
+        Round {
+            lost: boards.iter().any(|board| board.game_over),
+            won: boards.iter().all(|board| board.victory),
+            elapsed: start.map_or(Duration::ZERO, |start| end.unwrap_or_else(Instant::now).duration_since(start)),
+        }
+    }
+
+    fn over(&self) -> bool {
+        self.lost || self.won
+    }
+
+    fn final_time(&self) -> f64 {
+        rounded_time(self.elapsed)
+    }
+}
+
+/// The window title under `--title`: the board, clock and flags while
+/// playing, and the outcome with the final time once it's over.
+fn window_title(game: &Game, round: &Round) -> String {
+    let (seconds, timer) = (round.elapsed.as_secs(), game.config.timer);
+    match (round.lost, round.won) {
+        (true, _) => format!("扫雷 — 失败 — {:.2} 秒", round.final_time()),
+        (_, true) if timer => format!("扫雷 — 胜利 — {:.2} 秒", round.final_time()),
+        (_, true) => "扫雷 — 完成".to_string(),
+        _ if timer => format!(
+            "扫雷 — {}x{} — {}:{:02} — {} 旗",
+            game.width,
+            game.height,
+            seconds / 60,
+            seconds % 60,
+            game.flags
+        ),
+        _ => format!("扫雷 — {}x{} — {} 旗", game.width, game.height, game.flags),
+    }
+}
+
+/// Background for a revealed number in heat mode: a grayscale step from the
+/// 256-color ramp, two steps lighter per neighboring mine, so dense areas
+/// stand out without drowning the digits.
+fn heat_shade(cell: &Cell) -> Option<Color> {
+    if cell.state != CellState::Revealed || cell.is_mine || cell.neighbor_mines == 0 {
+        return None;
+    }
+    Some(Color::Indexed(232 + 2 * cell.neighbor_mines.min(8)))
+}
+
+/// A hidden cell's chance of being a mine under the odds overlay, in
+/// tenths: `0` for under 10%, up to `9` for 90% or more.
+fn odds_digit(chance: f64) -> char {
+    char::from_digit(((chance * 10.0) as u32).min(9), 10).unwrap_or('9')
+}
+
+/// Green for a fair bet, yellow for a risky one, red for a likely mine.
+fn odds_color(chance: f64) -> Color {
+    if chance < 0.2 {
+        Color::Green
+    } else if chance < 0.5 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+/// Planning-mode label for a revealed number: how many of its neighbors are
+/// already marked as mines out of how many it needs, e.g. `2/3`. The color
+/// is set once the count is met (green, ready to chord) or overshot (yellow).
+fn planning_label(game: &Game, x: usize, y: usize, cell: &Cell) -> Option<(String, Option<Color>)> {
+    if cell.state != CellState::Revealed || cell.is_mine || cell.neighbor_mines == 0 {
+        return None;
+    }
+    let marked = marked_around(game, x, y);
+    let needed = cell.neighbor_mines as usize;
+    let color = if marked == needed {
+        Some(Color::Green)
+    } else if marked > needed {
+        Some(Color::Yellow)
+    } else {
+        None
+    };
+    Some((format!("{}/{}", marked, needed), color))
+}
+
+/// Whether `cell` is a revealed number with as many marked mines around it
+/// as it counts, so chording it opens the rest.
+fn satisfied(game: &Game, x: usize, y: usize, cell: &Cell) -> bool {
+    cell.state == CellState::Revealed
+        && !cell.is_mine
+        && cell.neighbor_mines > 0
+        && marked_around(game, x, y) == cell.neighbor_mines as usize
+}
+
+/// Neighbors of `(x, y)` flagged or shown as mines.
+fn marked_around(game: &Game, x: usize, y: usize) -> usize {
+    game.neighbors(x, y).filter(|&(nx, ny)| game.get(nx, ny).marked_mine()).count()
+}
+
+/// What the status bar says about the revealed number under the cursor:
+/// whether its flags are all there, so it can be chorded, too many, or
+/// how many are still missing. The color follows `planning_label`.
+fn cursor_number_status(game: &Game) -> Option<(String, Option<Color>)> {
+    let (x, y) = (game.cursor_x, game.cursor_y);
+    let cell = game.get(x, y);
+    if cell.state != CellState::Revealed || cell.is_mine || cell.neighbor_mines == 0 {
+        return None;
+    }
+    let (marked, needed) = (marked_around(game, x, y), cell.neighbor_mines as usize);
+    Some(match marked.cmp(&needed) {
+        Ordering::Equal => ("已满足".to_string(), Some(Color::Green)),
+        Ordering::Greater => (format!("多了 {} 面旗", marked - needed), Some(Color::Yellow)),
+        Ordering::Less => (format!("还差 {} 面旗", needed - marked), None),
+    })
+}
+
+/// Boxes every cell of the rendered board rows in light grid lines, each
+/// column `cell_width` wide.
+fn with_grid(rows: Vec<Spans<'static>>, cell_width: usize) -> Vec<Spans<'static>> {
+    let style = Style::default().fg(theme::resolve(Color::DarkGray));
+    let columns = rows.first().map_or(0, |row| row.0.len());
+    let rule = |left: &str, middle: &str, right: &str| {
+        let segments = vec!["─".repeat(cell_width); columns];
+        Spans::from(Span::styled(format!("{}{}{}", left, segments.join(middle), right), style))
+    };
+    let mut lines = vec![rule("┌", "┬", "┐")];
+    for (y, row) in rows.into_iter().enumerate() {
+        if y > 0 {
+            lines.push(rule("├", "┼", "┤"));
+        }
+        let mut spans = Vec::new();
+        for cell in row.0 {
+            spans.push(Span::styled("│", style));
+            spans.push(cell);
+        }
+        spans.push(Span::styled("│", style));
+        lines.push(Spans::from(spans));
+    }
+    lines.push(rule("└", "┴", "┘"));
+    lines
+}
+
+/// How a cell is drawn once the game is lost, where that differs from play:
+///
+/// * a mine that was stepped on: bold white `*` on red;
+/// * any other mine still covered, `?` or not: red `*`;
+/// * a flag on a mine: green `✓`;
+/// * a flag on a safe cell: bold yellow `✗`.
+///
+/// Mines are `@` under a theme with `shapes`, the one stepped on reversed.
+fn post_mortem(game: &Game, x: usize, y: usize, cell: &Cell) -> Option<(char, Style)> {
+    let style = Style::default();
+    // Under `shapes` the red is backed by a glyph and reversed video.
+    let (mine, hit) = if theme::current().shapes {
+        (SHAPE_MINE, style.add_modifier(Modifier::REVERSED | Modifier::BOLD))
+    } else {
+        ('*', style.fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD))
+    };
+    match (cell.state, cell.is_mine) {
+        _ if game.exploded.contains(&(x, y)) => Some((mine, hit)),
+        (CellState::Hidden | CellState::Questioned, true) => Some((mine, style.fg(Color::Red))),
+        (CellState::Flagged, true) => Some(('✓', style.fg(Color::Green))),
+        (CellState::Flagged, false) => Some(('✗', style.fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        _ => None,
+    }
+}
+
+/// Passes of the event loop, about 16 ms each, that the mines of a lost
+/// board take to come into view.
+const EXPLOSION_FRAMES: usize = 45;
+
+/// The mines of a lost board coming into view a few at a time, nearest
+/// to the one stepped on first, with that one flashing. Advanced once per
+/// pass of the event loop; until it is done the board ignores play and any
+/// key skips to the end.
+struct Explosion {
+    /// Each mine's place in the order they appear.
+    order: HashMap<(usize, usize), usize>,
+    frame: usize,
+}
+
+impl Explosion {
+    /// `None` if no mine was stepped on, as when the other board of a
+    /// split game was lost.
+    fn of(game: &Game) -> Option<Self> {
+        let &(ox, oy) = game.exploded.last()?;
+        let mut mines: Vec<(usize, usize)> =
+            game.iter_cells().filter(|(_, _, cell)| cell.is_mine).map(|(x, y, _)| (x, y)).collect();
+        mines.sort_by_key(|&(x, y)| (x.abs_diff(ox).max(y.abs_diff(oy)), y, x));
+        let order = mines.into_iter().enumerate().map(|(rank, mine)| (mine, rank)).collect();
+        Some(Explosion { order, frame: 0 })
+    }
+
+    /// Moves on a frame; false once every mine is in view.
+    fn tick(&mut self) -> bool {
+        self.frame += 1;
+        self.frame < EXPLOSION_FRAMES
+    }
+
+    fn shown(&self, x: usize, y: usize) -> bool {
+        let count = ((self.frame + 1) * self.order.len()).div_ceil(EXPLOSION_FRAMES);
+        self.order.get(&(x, y)).is_some_and(|&rank| rank < count)
+    }
+
+    /// Whether the mine stepped on is lit in this frame.
+    fn flash(&self) -> bool {
+        (self.frame / 6).is_multiple_of(2)
+    }
+}
+
+/// Frame timing shown in the corner by `--debug-perf`.
+struct PerfStats {
+    visible: bool,
+    last_frame: Instant,
+    /// Time spent inside the most recent `terminal.draw` call.
+    draw_time: Duration,
+    /// Redraws per second, exponentially smoothed.
+    fps: f64,
+}
+
+impl PerfStats {
+    fn new(visible: bool) -> Self {
+        Self { visible, last_frame: Instant::now(), draw_time: Duration::ZERO, fps: 0.0 }
+    }
+
+    fn record(&mut self, draw_time: Duration) {
+        let now = Instant::now();
+        let interval = now.duration_since(self.last_frame).as_secs_f64();
+        self.last_frame = now;
+        self.draw_time = draw_time;
+        if interval > 0.0 {
+            let rate = 1.0 / interval;
+            self.fps = if self.fps == 0.0 { rate } else { self.fps * 0.9 + rate * 0.1 };
+        }
+    }
+}
+
+/// How the board is laid out in the terminal, before clipping to the screen.
+struct BoardLayout {
+    /// Columns per cell, not counting grid lines.
+    cell_width: u16,
+    /// Columns odd hex rows are pushed right by.
+    stagger: u16,
+    width: u16,
+    height: u16,
+}
+
+impl BoardLayout {
+    fn new(game: &Game, options: &Options, planning: bool) -> Self {
+        // The grid lines take over from the separating space.
+        let mut cell_width = game.cell_width(options.compact || options.grid);
+        if planning {
+            // Room for the flagged count and the slash in front.
+            cell_width += game.cell_width(true) + 1;
+        }
+        if options.grid && !options.compact {
+            // A space either side of the symbol, inside the lines.
+            cell_width += 2;
+        }
+        // With `--grid` every cell also takes one line to its left and one
+        // above, plus the closing line on the far side.
+        let line = options.grid as u16;
+        let stagger = match game.config.topology {
+            Topology::Square => 0,
+            Topology::Hex => cell_width / 2,
+        };
+        BoardLayout {
+            cell_width,
+            stagger,
+            width: game.width as u16 * (cell_width + line) + line + stagger,
+            height: game.height as u16 * (1 + line) + line,
+        }
+    }
+
+    /// Where cell `(x, y)` sits within the board, as its first column and
+    /// its row.
+    fn cell_position(&self, x: usize, y: usize, options: &Options) -> (u16, u16) {
+        let line = options.grid as u16;
+        let indent = if y % 2 == 1 { self.stagger } else { 0 };
+        (indent + x as u16 * (self.cell_width + line) + line, y as u16 * (1 + line) + line)
+    }
+}
+
+/// A board set aside with the new-board key, with what the main loop knew
+/// about it, until Tab brings it back.
+struct Parked {
+    game: Game,
+    viewport: Viewport,
+    /// Counted from 1 in the order the boards were started.
+    number: usize,
+    recorded: bool,
+    new_record: bool,
+    replay: bool,
+}
+
+/// Cells kept between the cursor and the edge of a board that doesn't fit.
+const VIEWPORT_MARGIN: u16 = 2;
+
+/// The top-left corner of the part of the board on screen, when the board
+/// is larger than the terminal. Kept from frame to frame so the view only
+/// moves when the cursor nears an edge.
+#[derive(Default)]
+struct Viewport {
+    x: u16,
+    y: u16,
+}
+
+impl Viewport {
+    /// Scrolls just enough to keep the cursor `VIEWPORT_MARGIN` cells inside
+    /// a `visible` area, and never past the board's far edges, so growing
+    /// the terminal again leaves no empty gutter.
+    fn follow(&mut self, game: &Game, layout: &BoardLayout, options: &Options, visible: (u16, u16)) {
+        let (column, row) = layout.cell_position(game.cursor_x, game.cursor_y, options);
+        let step = layout.cell_width + options.grid as u16;
+        self.x = follow_axis(self.x, column, layout.cell_width, visible.0, layout.width, VIEWPORT_MARGIN * step);
+        self.y = follow_axis(
+            self.y,
+            row,
+            1,
+            visible.1,
+            layout.height,
+            VIEWPORT_MARGIN * (1 + options.grid as u16),
+        );
+    }
+}
+
+/// One axis of `Viewport::follow`: the new offset for a view `visible` long
+/// over `content`, keeping `start..start + len` at least `margin` from
+/// either edge where the view allows.
+fn follow_axis(offset: u16, start: u16, len: u16, visible: u16, content: u16, margin: u16) -> u16 {
+    if content <= visible {
+        return 0;
+    }
+    let margin = margin.min(visible.saturating_sub(len) / 2);
+    let mut offset = offset;
+    if start < offset + margin {
+        offset = start.saturating_sub(margin);
+    }
+    if start + len + margin > offset + visible {
+        offset = start + len + margin - visible;
+    }
+    offset.min(content - visible)
+}
+
+/// The toggles that change how a board is drawn.
+struct BoardLook<'a> {
+    options: &'a Options,
+    cursor: bool,
+    heat: bool,
+    planning: bool,
+    odds: bool,
+    /// Dim the numbers whose flags are all placed.
+    dim_satisfied: bool,
+    /// Set while a lost board's mines are still coming into view.
+    explosion: Option<&'a Explosion>,
+    /// The border color of a `--bell` flash, while it lasts.
+    flash: Option<Color>,
+}
+
+/// Draws `game` framed under `title` in `area`: centered when it fits, and
+/// scrolled by `viewport` to follow the cursor when it doesn't.
+fn draw_board<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    title: Span,
+    game: &Game,
+    look: &BoardLook,
+    viewport: &mut Viewport,
+) {
+    let board_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(look.flash.unwrap_or(theme::current().border)))
+        .title(title);
+    let inner = board_block.inner(area);
+    f.render_widget(board_block, area);
+
+    let board = BoardLayout::new(game, look.options, look.planning);
+    let BoardLayout { cell_width, stagger, width, height } = board;
+    // A frame of its own shows where the cells end, unless `--grid` already
+    // closes them in or there is no room left for one.
+    let frame = (!look.options.grid && inner.width > 2 && inner.height > 2) as u16;
+    let board_width = width.min(inner.width - 2 * frame);
+    let board_height = height.min(inner.height - 2 * frame);
+    viewport.follow(game, &board, look.options, (board_width, board_height));
+    let start_x = inner.x + (inner.width - board_width) / 2;
+    let start_y = inner.y + (inner.height - board_height) / 2;
+    if frame == 1 {
+        let grid_frame = Block::default().borders(Borders::ALL).border_style(Style::default().fg(theme::current().border));
+        f.render_widget(grid_frame, Rect::new(start_x - 1, start_y - 1, board_width + 2, board_height + 2));
+    }
+
+    // Worked out once per frame, and not over a finished board.
+    let odds = (look.odds && !game.game_over && !game.victory).then(|| game.mine_probabilities());
+    let rows: Vec<Spans> = game.rows().enumerate().map(|(y, row)| {
+        let indent = Span::raw(" ".repeat(if y % 2 == 1 { stagger as usize } else { 0 }));
+        Spans::from(std::iter::once(indent).chain(row.iter().enumerate().map(|(x, cell)| {
+            let highlighted = look.cursor && x == game.cursor_x && y == game.cursor_y;
+            let in_view = |explosion: &Explosion| {
+                let hit = game.exploded.contains(&(x, y));
+                explosion.shown(x, y) && (!hit || explosion.flash())
+            };
+            let lost = game.game_over && look.explosion.is_none_or(in_view);
+            let (symbol, style) = match post_mortem(game, x, y, cell).filter(|_| lost) {
+                Some((glyph, style)) => (glyph.to_string(), style),
+                None if !highlighted && game.selected(x, y) => {
+                    (cell_symbol(game, cell), Style::default().fg(Color::Black).bg(Color::Cyan))
+                }
+                None if game.hint == Some((x, y)) && cell.covered() => {
+                    (cell_symbol(game, cell), Style::default().fg(Color::Black).bg(Color::Green))
+                }
+                None => match heat_shade(cell).filter(|_| look.heat && !highlighted) {
+                    Some(shade) => (cell_symbol(game, cell), cell_style(cell, false).bg(shade)),
+                    None => (cell_symbol(game, cell), cell_style(cell, highlighted)),
+                },
+            };
+            let (symbol, style) = match planning_label(game, x, y, cell).filter(|_| look.planning) {
+                Some((label, Some(color))) => (label, style.fg(theme::resolve(color))),
+                Some((label, None)) => (label, style),
+                None => (symbol, style),
+            };
+            // The cursor keeps its full highlight over a satisfied number.
+            let style = if look.dim_satisfied && !highlighted && satisfied(game, x, y, cell) {
+                style.add_modifier(Modifier::DIM)
+            } else {
+                style
+            };
+            let chance = odds.as_ref().filter(|_| cell.covered()).and_then(|odds| odds.get(&(x, y)));
+            let (symbol, style) = match chance {
+                Some(&chance) => {
+                    let style = style.fg(theme::resolve(odds_color(chance)));
+                    (odds_digit(chance).to_string(), if highlighted { style } else { style.bg(Color::Indexed(236)) })
+                }
+                None => (symbol, style),
+            };
+            let text = if look.options.grid {
+                format!("{:^width$}", symbol, width = cell_width as usize)
+            } else {
+                format!("{:<width$}", symbol, width = cell_width as usize)
+            };
+            Span::styled(text, style)
+        })).collect::<Vec<_>>())
+    }).collect();
+    let rows = if look.options.grid { with_grid(rows, cell_width as usize) } else { rows };
+    f.render_widget(
+        Paragraph::new(rows).scroll((viewport.y, viewport.x)),
+        Rect::new(start_x, start_y, board_width, board_height),
+    );
+}
+
+/// A `width` x `height` rectangle centered in `area`, shrunk to fit if needed.
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Built-in presets; `config.toml` can override each of them.
+const DIFFICULTIES: [(usize, usize, usize); 3] = [(8, 8, 10), (16, 16, 40), (24, 20, 99)];
+const LABELS: [&str; 3] = ["初级", "中级", "高级"];
+
+/// The built-in presets as the menu lists them.
+fn builtin_difficulties() -> [Difficulty; 3] {
+    std::array::from_fn(|index| {
+        let (width, height, mines) = DIFFICULTIES[index];
+        Difficulty { name: LABELS[index].to_string(), width, height, mines }
+    })
+}
+
+/// The difficulty index and custom board a menu answer starts. Presets
+/// after the built-in three are played as custom boards, so they have no
+/// best times of their own.
+fn menu_board(choice: MenuChoice, difficulties: &[Difficulty]) -> (usize, Option<(usize, usize, usize)>) {
+    match choice {
+        MenuChoice::Difficulty(index) if index < LABELS.len() => (index, None),
+        MenuChoice::Difficulty(index) => (0, Some(difficulties[index].size())),
+        MenuChoice::Custom(board) => (0, Some(board)),
+    }
+}
+
+/// Difficulty used by the daily challenge, so everyone plays the same board.
+const DAILY_DIFFICULTY: usize = 1;
+
+struct Options {
+    daily: bool,
+    reveal_budget: usize,
+    max_cells: usize,
+    strict_flags: bool,
+    strategy: Option<String>,
+    symmetry: Symmetry,
+    big_opening: bool,
+    /// Keep the first row and column free of mines.
+    assist: bool,
+    /// Time between the moves of a held movement key (`--repeat-ms`).
+    repeat: Option<Duration>,
+    debug_perf: bool,
+    analyze: bool,
+    compact: bool,
+    lives: Option<u32>,
+    safe_reveals: u32,
+    export_image: Option<PathBuf>,
+    /// Where to write the final game state as JSON; `-` is stdout.
+    dump_state: Option<PathBuf>,
+    /// Where to write the solution grid at exit; `-` is stdout.
+    dump: Option<PathBuf>,
+    /// Where to write the moves of the last game at exit.
+    record: Option<PathBuf>,
+    /// A `--replay` recording; its board is in `code`.
+    playback: Option<Recording>,
+    /// `--width`, `--height` and `--mines`, given together, or the size of
+    /// a `--load-board` grid.
+    custom: Option<(usize, usize, usize)>,
+    /// The `--load-board` grid, whose mines the first game is laid with.
+    board: Option<Game>,
+    seed: Option<u64>,
+    code: Option<BoardCode>,
+    adaptive: bool,
+    gentle: bool,
+    no_cursor_highlight: bool,
+    no_dim_satisfied: bool,
+    no_guess: bool,
+    verbose: bool,
+    /// Index into the presets, skipping the menu.
+    difficulty: Option<usize>,
+    quiet: bool,
+    heat: bool,
+    keys: KeyBindings,
+    /// Index into `theme::THEMES`, over the saved one until the settings
+    /// screen picks another.
+    theme: Option<usize>,
+    win_by_flags: bool,
+    resume: bool,
+    /// A file from the save key instead of the autosave.
+    resume_from: Option<PathBuf>,
+    clustering: f64,
+    grid: bool,
+    hex: bool,
+    practice: bool,
+    zen: bool,
+    /// Boards without mines, as `Game::new_zen`.
+    no_mines: bool,
+    auto_complete: bool,
+    auto_first: AutoFirst,
+    first_cell: Option<FirstCell>,
+    sound: bool,
+    bell: bool,
+    /// Show the moves made against the board's 3BV as a target.
+    min_moves: bool,
+    title: bool,
+    split: bool,
+    log: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            daily: false,
+            reveal_budget: DEFAULT_REVEAL_BUDGET,
+            max_cells: DEFAULT_MAX_CELLS,
+            strict_flags: false,
+            strategy: None,
+            symmetry: Symmetry::None,
+            big_opening: false,
+            assist: false,
+            repeat: None,
+            debug_perf: false,
+            analyze: false,
+            compact: false,
+            lives: None,
+            safe_reveals: 0,
+            export_image: None,
+            dump_state: None,
+            dump: None,
+            record: None,
+            playback: None,
+            custom: None,
+            board: None,
+            seed: None,
+            code: None,
+            adaptive: false,
+            gentle: false,
+            no_cursor_highlight: false,
+            no_dim_satisfied: false,
+            no_guess: false,
+            verbose: false,
+            difficulty: None,
+            quiet: false,
+            heat: false,
+            keys: KeyBindings::default(),
+            theme: None,
+            win_by_flags: false,
+            resume: false,
+            resume_from: None,
+            clustering: 0.0,
+            grid: false,
+            hex: false,
+            practice: false,
+            zen: false,
+            no_mines: false,
+            auto_complete: false,
+            auto_first: AutoFirst::Off,
+            first_cell: None,
+            sound: false,
+            bell: false,
+            min_moves: false,
+            title: false,
+            split: false,
+            log: None,
+        }
+    }
+}
+
+
+/// Extra arguments read before the command line, split on whitespace.
+const OPTS_VAR: &str = "MINESWEEPER_OPTS";
+
+impl Options {
+    /// Options from `MINESWEEPER_OPTS` followed by the command line, parsed
+    /// as one argument list. Later arguments win, so the command line takes
+    /// precedence for anything that takes a value; switches like `--daily`
+    /// set in the variable can't be turned off again. An unset or empty
+    /// variable adds nothing.
+    fn from_env() -> Result<Self, String> {
+        let extra = env::var(OPTS_VAR).unwrap_or_default();
+        let extra: Vec<String> = extra.split_whitespace().map(String::from).collect();
+        Self::parse(extra.into_iter().chain(env::args().skip(1)))
+    }
+
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let (mut width, mut height, mut mines): (Option<usize>, Option<usize>, Option<usize>) = (None, None, None);
+        let mut vim = false;
+        let mut wrap = false;
+        let mut playback = None;
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => width = Some(parse_value(&arg, args.next())?),
+                "--height" => height = Some(parse_value(&arg, args.next())?),
+                "--mines" => mines = Some(parse_value(&arg, args.next())?),
+                "--seed" => options.seed = Some(parse_value(&arg, args.next())?),
+                "--daily" => options.daily = true,
+                "--strict-flags" => options.strict_flags = true,
+                "--big-opening" => options.big_opening = true,
+                "--assist" => options.assist = true,
+                "--debug-perf" => options.debug_perf = true,
+                "--analyze" => options.analyze = true,
+                "--compact" => options.compact = true,
+                "--grid" => options.grid = true,
+                "--hex" => options.hex = true,
+                "--practice" => options.practice = true,
+                "--zen" => options.zen = true,
+                "--no-mines" => options.no_mines = true,
+                "--auto-complete" => options.auto_complete = true,
+                "--sound" => options.sound = true,
+                "--bell" => options.bell = true,
+                "--min-moves" => options.min_moves = true,
+                "--title" => options.title = true,
+                "--split" => options.split = true,
+                "--auto-first" => options.auto_first = AutoFirst::Waiting,
+                "--auto-first-timed" => options.auto_first = AutoFirst::Timed,
+                "--adaptive" => options.adaptive = true,
+                "--gentle" => options.gentle = true,
+                "--no-cursor-highlight" => options.no_cursor_highlight = true,
+                "--no-dim-satisfied" => options.no_dim_satisfied = true,
+                "--no-guess" => options.no_guess = true,
+                "--verbose" => options.verbose = true,
+                "--quiet" => options.quiet = true,
+                "--heat" => options.heat = true,
+                "--win-by-flags" => options.win_by_flags = true,
+                // The file is optional, so a following option isn't taken as one.
+                "--resume" => {
+                    options.resume = true;
+                    options.resume_from = args.next_if(|next| !next.starts_with("--")).map(PathBuf::from);
+                }
+                "--layout" => {
+                    let name: String = parse_value(&arg, args.next())?;
+                    options.keys = KeyBindings::for_layout(&name).ok_or_else(|| {
+                        format!("未知键盘布局: {} (可选: {})", name, keys::LAYOUT_NAMES.join(", "))
+                    })?;
+                }
+                "--vim" => vim = true,
+                "--wrap" => wrap = true,
+                "--theme" => {
+                    let name: String = parse_value(&arg, args.next())?;
+                    options.theme = Some(theme::THEME_NAMES.iter().position(|&known| known == name).ok_or_else(|| {
+                        format!("未知主题: {} (可选: {})", name, theme::THEME_NAMES.join(", "))
+                    })?);
+                }
+                "--difficulty" => {
+                    // Checked against the configured difficulties once they are loaded.
+                    let choice: usize = parse_value(&arg, args.next())?;
+                    if choice == 0 {
+                        return Err("--difficulty 必须大于 0".to_string());
+                    }
+                    options.difficulty = Some(choice - 1);
+                }
+                "--safe-reveals" => options.safe_reveals = parse_value(&arg, args.next())?,
+                "--lives" => {
+                    let lives = parse_value(&arg, args.next())?;
+                    if lives == 0 {
+                        return Err("--lives 必须大于 0".to_string());
+                    }
+                    options.lives = Some(lives);
+                }
+                "--code" => {
+                    let code: String = parse_value(&arg, args.next())?;
+                    options.code = Some(BoardCode::decode(&code)?);
+                }
+                "--clustering" => {
+                    let factor: f64 = parse_value(&arg, args.next())?;
+                    if !(-MAX_CLUSTERING..=MAX_CLUSTERING).contains(&factor) {
+                        return Err(format!("--clustering 必须在 -{0} 到 {0} 之间", MAX_CLUSTERING));
+                    }
+                    // Hundredths are all a board code keeps.
+                    options.clustering = (factor * 100.0).round() / 100.0;
+                }
+                "--log" => options.log = Some(parse_value(&arg, args.next())?),
+                "--export-image" => options.export_image = Some(parse_value(&arg, args.next())?),
+                "--dump-state" => options.dump_state = Some(parse_value(&arg, args.next())?),
+                "--dump" => options.dump = Some(parse_value(&arg, args.next())?),
+                "--record" => options.record = Some(parse_value(&arg, args.next())?),
+                "--load-board" => {
+                    let path: PathBuf = parse_value(&arg, args.next())?;
+                    let text = fs::read_to_string(&path).map_err(|err| format!("无法读取 {}: {}", path.display(), err))?;
+                    let board = Game::from_ascii(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+                    options.board = Some(board);
+                }
+                "--replay" => {
+                    let path: PathBuf = parse_value(&arg, args.next())?;
+                    playback = Some(Recording::load(&path)?);
+                }
+                "--first-cell" => {
+                    let name: String = parse_value(&arg, args.next())?;
+                    let cell = name.parse().map_err(|_| format!("未知起始格: {} (可选: center, top-left)", name))?;
+                    options.first_cell = Some(cell);
+                }
+                "--symmetry" => {
+                    let name: String = parse_value(&arg, args.next())?;
+                    options.symmetry = name.parse().map_err(|_| {
+                        format!("未知对称方式: {} (可选: horizontal, vertical, rotational)", name)
+                    })?;
+                }
+                "--strategy" => {
+                    let name: String = parse_value(&arg, args.next())?;
+                    if strategy::by_name(&name).is_none() {
+                        return Err(format!(
+                            "未知策略: {} (可选: {})",
+                            name,
+                            strategy::STRATEGY_NAMES.join(", ")
+                        ));
+                    }
+                    options.strategy = Some(name);
+                }
+                "--repeat-ms" => {
+                    let ms: u64 = parse_value(&arg, args.next())?;
+                    if ms == 0 {
+                        return Err("--repeat-ms 必须大于 0".to_string());
+                    }
+                    options.repeat = Some(Duration::from_millis(ms));
+                }
+                "--reveal-budget" => {
+                    options.reveal_budget = parse_value(&arg, args.next())?;
+                    if options.reveal_budget == 0 {
+                        return Err("--reveal-budget 必须大于 0".to_string());
+                    }
+                }
+                "--max-cells" => {
+                    options.max_cells = parse_value(&arg, args.next())?;
+                    if options.max_cells == 0 {
+                        return Err("--max-cells 必须大于 0".to_string());
+                    }
+                }
+                other => return Err(format!("未知参数: {}", other)),
+            }
+        }
+        // A one-column cell can't be staggered by half, and the grid lines
+        // are square.
+        if options.hex && (options.compact || options.grid) {
+            return Err("--hex 不能与 --compact 或 --grid 同时使用".to_string());
+        }
+        // After the loop, since `--layout` replaces the whole profile.
+        options.keys.vim = vim;
+        options.keys.wrap = wrap;
+        options.custom = match (width, height, mines) {
+            (None, None, None) => None,
+            (Some(width), Some(height), Some(mines)) => Some(custom_board(width, height, mines)?),
+            _ => return Err("--width、--height 和 --mines 需要同时给出".to_string()),
+        };
+        // Both bring their own seed.
+        if options.seed.is_some() && (options.daily || options.code.is_some()) {
+            return Err("--seed 不能与 --daily 或 --code 同时使用".to_string());
+        }
+        // A custom board is its own size, unlike these.
+        if options.custom.is_some() && (options.daily || options.code.is_some() || options.difficulty.is_some()) {
+            return Err("--width/--height/--mines 不能与 --daily、--code 或 --difficulty 同时使用".to_string());
+        }
+        // The grid is a custom board of its own size.
+        if let Some(board) = &options.board {
+            let other_board = options.custom.is_some() || options.daily || options.code.is_some() || options.difficulty.is_some();
+            if other_board || options.resume || options.split || playback.is_some() {
+                return Err("--load-board 不能与 --width/--height/--mines、--daily、--code、--difficulty、--resume、--split 或 --replay 同时使用".to_string());
+            }
+            options.custom = Some(custom_board(board.width, board.height, board.mines)?);
+        }
+        // Each of these is about one board: a fixed one, a saved one, or one
+        // the next board is built from.
+        if options.split
+            && (options.daily || options.code.is_some() || options.resume || options.strategy.is_some() || options.adaptive)
+        {
+            return Err("--split 不能与 --daily、--code、--resume、--strategy 或 --adaptive 同时使用".to_string());
+        }
+        // A resumed game's earlier moves are lost, and a split game is two.
+        if options.record.is_some() && (options.resume || options.split) {
+            return Err("--record 不能与 --resume 或 --split 同时使用".to_string());
+        }
+        // A replay brings its own board and plays nothing but the recording.
+        if let Some((recording, code)) = playback {
+            let other_board = options.daily || options.code.is_some() || options.custom.is_some() || options.seed.is_some();
+            if other_board || options.resume || options.split || options.strategy.is_some() || options.adaptive {
+                return Err(
+                    "--replay 不能与 --daily、--code、--width/--height/--mines、--seed、--resume、--split、--strategy 或 --adaptive 同时使用"
+                        .to_string(),
+                );
+            }
+            options.code = Some(code);
+            options.playback = Some(recording);
+        }
+        Ok(options)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} 需要一个参数", flag))?;
+    value.parse().map_err(|_| format!("{} 的参数无效: {}", flag, value))
+}
+
+const USAGE: &str = "\
+用法: minesweeper [选项]
+  --difficulty <n>        直接选择难度菜单中的第 n 项，不显示菜单
+  --seed <n>              用给定的种子布雷，同一种子总是得到同一棋盘
+  --width <n> --height <n> --mines <n>
+                          自定义棋盘尺寸与地雷数，不显示菜单
+  --quiet                 不显示难度菜单 (从管道读取难度，否则为初级)
+  --daily                 每日挑战
+  --reveal-budget <n>     每帧最多展开的空白格数
+  --repeat-ms <n>         按住方向键时每 n 毫秒移动一格 (需要终端报告按键松开)
+  --max-cells <n>         棋盘格数上限，默认 1000000
+  --strict-flags          旗帜数不能超过地雷数
+  --strategy <name>       自动游玩 (random, deduction)
+  --symmetry <kind>       对称布雷 (horizontal, vertical, rotational)
+  --big-opening           首次翻开的 3x3 内没有地雷
+  --assist                第一行和第一列没有地雷，适合新手
+  --auto-first            开局自动翻开一个安全的起始格，第一次操作时才开始计时
+  --auto-first-timed      同 --auto-first，但自动翻开时就开始计时
+  --first-cell <pos>      每局都从固定的格子开始并自动翻开 (center, top-left)
+  --clustering <factor>   地雷聚集 (正数) 或分散 (负数) 的程度，-5 到 5，默认 0
+  --lives <n>             踩雷时扣除生命而不是直接结束
+  --safe-reveals <n>      每局可以用 F12 安全翻开 n 次，保证不会踩雷
+  --practice              练习模式: 踩雷只记为失误，可以继续游戏
+  --zen                   禅模式: 不计时，踩到的地雷自动插旗，只有通关才结束
+  --no-mines              没有地雷的禅模式棋盘，用来熟悉操作
+  --auto-complete         获胜时自动给剩下的地雷插旗
+  --gentle                翻开时连同周围 8 格一起翻开
+  --no-guess              生成无需猜测即可解开的棋盘
+  --verbose               一直显示无猜棋盘的生成耗时与重试次数
+  --compact               每个格子只占一列
+  --grid                  在格子之间画出网格线
+  --hex                   六边形棋盘，每个格子有 6 个相邻格
+  --analyze               在状态栏显示最大开口并列出 50/50 陷阱
+  --debug-perf            显示帧耗时
+  --no-cursor-highlight   不高亮光标所在的格子 (F4 切换)
+  --no-dim-satisfied      不调暗旗帜已插够的数字 (F11 切换)
+  --win-by-flags          正确插满所有地雷也算获胜
+  --layout <name>         按键盘布局调整字母键 (qwerty, azerty, dvorak)
+  --theme <name>          配色主题 (classic, dark, high-contrast, mono, colorblind)
+  --vim                   也可以用 hjkl 移动光标 (Dvorak 布局为 dhtn)
+  --wrap                  光标移出棋盘一边时从对边出现
+  --heat                  按数字大小给已翻开的数字格加深背景 (F5 切换)
+  --export-image <file>   退出时把棋盘导出为 SVG
+  --dump-state <file>     退出时把完整的游戏状态写成 JSON (- 表示标准输出)
+  --dump <file>           退出时把棋盘答案写成文本网格 (- 表示标准输出)
+  --load-board <file>     用 --dump 写下的文本网格作为第一局的棋盘
+  --record <file>         退出时把最后一局的每一步写入录像文件
+  --replay <file>         按录像中的时间重放一局 (←/→ 逐步, Esc 退出)
+  --sound                 翻开格子时发出提示音，音高随周围地雷数升高
+  --bell                  踩雷时响铃并让边框闪红，获胜时边框闪绿
+  --min-moves             最少步数挑战: 在状态栏显示步数与 3BV，步数不多于 3BV 获胜即为完美
+  --title                 在终端窗口标题中显示棋盘、时间与旗帜数
+  --split                 并排同时玩两个棋盘，Tab 切换
+  --log <file>            把对局事件 (新棋盘、翻开、插旗、胜负) 追加写入日志文件
+  --code <code>           按分享的棋盘代码重建棋盘
+  --adaptive              每局结束后按胜负调整下一局的地雷数
+  --resume [file]         继续上次未完成的对局 (自动存档)，或按保存键写下的文件
+
+环境变量 MINESWEEPER_OPTS 中的选项会先于命令行读取，命令行中的同名选项优先。
+";
+
+/// Today's daily challenge. Once it has been won it can still be played as
+/// practice, but the time is no longer recorded.
+struct Daily {
+    date: String,
+    practice: bool,
+}
+
+/// A custom board's size, as `--width`/`--height`/`--mines` and the menu
+/// take it.
+fn custom_board(width: usize, height: usize, mines: usize) -> Result<(usize, usize, usize), String> {
+    if width == 0 || height == 0 {
+        return Err("宽度和高度必须大于 0".to_string());
+    }
+    if mines == 0 || width.checked_mul(height).is_none_or(|cells| mines >= cells) {
+        return Err(format!("地雷数必须大于 0 且小于格子数 ({}x{})", width, height));
+    }
+    Ok((width, height, mines))
+}
+
+/// `--quiet` startup: a difficulty piped on stdin is read without printing
+/// the menu, and an interactive stdin is not waited on at all.
+fn read_difficulty_quietly() -> io::Result<usize> {
+    if io::stdin().is_terminal() {
+        return Ok(0);
+    }
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(parse_difficulty(&input))
+}
+
+/// Index for a menu answer of `1`-`3`; anything else is beginner.
+fn parse_difficulty(input: &str) -> usize {
+    match input.trim().parse::<usize>() {
+        Ok(n @ 1..=3) => n - 1,
+        _ => 0,
+    }
+}
+
+/// The stats screen's table: one row per difficulty. The header is padded
+/// by hand, since each Chinese character takes two columns.
+fn stats_lines(tallies: &[Tally; 3]) -> Vec<Spans<'static>> {
+    let mut lines = vec![Spans::from(""), Spans::from("难度      已玩    胜利    胜率    连胜  最长连胜")];
+    for (label, tally) in LABELS.iter().zip(tallies) {
+        let rate = tally.win_rate().map_or("-".to_string(), |rate| format!("{:.1}%", rate * 100.0));
+        lines.push(Spans::from(format!(
+            "{}  {:>8}{:>8}{:>8}{:>8}{:>10}",
+            label, tally.played, tally.won, rate, tally.streak, tally.best_streak
+        )));
+    }
+    lines
+}
+
+/// `text` padded with spaces to `width` columns, counting each Chinese
+/// character as two.
+fn pad(text: &str, width: usize) -> String {
+    let shown = Span::raw(text).width();
+    format!("{}{}", text, " ".repeat(width.saturating_sub(shown)))
+}
+
+/// The help overlay: every key with what it does, in two columns, built
+/// from `keys` so a remapped key shows up as it is bound.
+fn help_lines(keys: &KeyBindings) -> Vec<Spans<'static>> {
+    let name = |c: char| if c == ' ' { "空格".to_string() } else { c.to_string() };
+    let moves = if keys.vim { format!("方向键/{}", keys.moves.iter().collect::<String>()) } else { "方向键".to_string() };
+    let board = [
+        (moves, "移动光标"),
+        ("Home/End".to_string(), "行首/行尾"),
+        ("PgUp/PgDn".to_string(), "列首/列尾"),
+        (name(keys.reveal), "翻开"),
+        (name(keys.flag), "插旗/?/取消"),
+        (name(keys.chord), "快速翻开"),
+        (name(keys.undo), "撤销插旗"),
+        (name(keys.select), "矩形选区"),
+        (format!("Tab/{}", name(keys.menu)), "交换翻开与插旗"),
+        (name(keys.auto_flag), "自动插旗"),
+        (name(keys.hint()), "提示安全格"),
+        (name(keys.frontier), "跳到待解格子"),
+        (name(keys.odds), "概率提示"),
+        ("Enter".to_string(), "自动完成"),
+        (name(keys.pause), "暂停"),
+        (name(keys.save), "保存"),
+        (name(keys.restart), "重新开始"),
+        (name(keys.retry), "重试本局"),
+    ];
+    let other = [
+        (name(keys.stats), "统计"),
+        (name(keys.leaderboard), "排行榜"),
+        (name(keys.new_board), "新棋盘"),
+        ("F2".to_string(), "剩余安全格"),
+        ("F3".to_string(), "帧耗时"),
+        ("F4".to_string(), "光标高亮"),
+        ("F5".to_string(), "热度着色"),
+        ("F6".to_string(), "规划模式"),
+        ("F7".to_string(), "复盘 (结束后)"),
+        ("F8".to_string(), "区域雷数"),
+        ("F9".to_string(), "辅助猜测"),
+        ("F10".to_string(), "计时显示"),
+        ("F11".to_string(), "调暗已满足数字"),
+        ("F12".to_string(), "安全翻开"),
+        ("1/2/3".to_string(), "切换难度 (结束后)"),
+        (name(keys.menu), "菜单 (结束后)"),
+        (format!("{}/Esc", name(keys.quit)), "退出"),
+        (name(keys.help), "帮助"),
+    ];
+    let key_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut lines = vec![Spans::from("")];
+    for ((left_key, left), (right_key, right)) in board.iter().zip(&other) {
+        lines.push(Spans::from(vec![
+            Span::styled(pad(left_key, 12), key_style),
+            Span::raw(pad(left, 18)),
+            Span::styled(pad(right_key, 8), key_style),
+            Span::raw(pad(right, 18)),
+        ]));
+    }
+    lines
+}
+
+/// The leaderboard screen for one difficulty: the three as tabs, then its
+/// entries from `scroll` on. Padded by hand like `stats_lines`.
+fn leaderboard_lines(scores: &Scores, difficulty: usize, scroll: usize) -> Vec<Spans<'static>> {
+    let tabs: Vec<Span> = LABELS
+        .iter()
+        .enumerate()
+        .map(|(index, label)| {
+            let style = if index == difficulty {
+                Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Span::styled(format!(" {} ", label), style)
+        })
+        .collect();
+    let mut lines = vec![Spans::from(""), Spans::from(tabs), Spans::from("")];
+    let entries = &scores.leaderboard[difficulty];
+    if entries.is_empty() {
+        lines.push(Spans::from("还没有记录"));
+    } else {
+        lines.push(Spans::from("名次      用时  日期        名字        "));
+        for (rank, entry) in entries.iter().enumerate().skip(scroll) {
+            lines.push(Spans::from(format!("{:>4}  {:>7.2}s  {}  {:<12}", rank + 1, entry.time, entry.date, entry.name)));
+        }
+    }
+    lines
+}
+
+/// Requests from `handle_key` that need more than the current `Game`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Command {
+    Quit,
+    /// A new board at the current difficulty.
+    Restart,
+    /// The board being played, from its seed.
+    Retry,
+    /// A new board at the given difficulty index.
+    Difficulty(usize),
+    /// Back to the difficulty menu.
+    Menu,
+    /// Show or hide the frame timing overlay.
+    TogglePerf,
+    /// Show or hide the safe-cells-left counter.
+    ToggleSafeLeft,
+    /// Swap what space and `f` do.
+    TogglePrimary,
+    /// Show or hide the cursor highlight.
+    ToggleCursor,
+    /// Turn the neighbor-count background tint on or off.
+    ToggleHeat,
+    /// Dim or stop dimming the numbers ready to chord.
+    ToggleDimSatisfied,
+    /// Show the list of keys over the board.
+    Help,
+    /// Show or hide flagged/needed counts on revealed numbers.
+    TogglePlanning,
+    /// Step through the moves of the finished game.
+    Review,
+    /// Show or hide the mines each frontier region still needs.
+    ToggleRegions,
+    /// Show or hide each hidden cell's chance of being a mine.
+    ToggleOdds,
+    /// Switch the clock between time taken and time left.
+    ToggleTimerMode,
+    /// Stop the clock and hide the board until the pause key comes again.
+    Pause,
+    /// Flag the mines single numbers force, and say how many.
+    AutoFlag,
+    /// Point out a safe cell, or open the one pointed out.
+    Hint,
+    /// Write the game to a file for `--resume <file>`.
+    Save,
+    /// Show the games played and won until the next key.
+    Stats,
+    /// Show the fastest named wins.
+    Leaderboard,
+    /// Set the board aside and start another at the same difficulty.
+    NewBoard,
+}
+
+/// What a key does to the cell under the cursor. Space performs the primary
+/// action and `f` the other one, so flag-first players can swap them.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Action {
+    Reveal,
+    Flag,
+}
+
+impl Action {
+    fn other(self) -> Self {
+        match self {
+            Action::Reveal => Action::Flag,
+            Action::Flag => Action::Reveal,
+        }
+    }
+
+    /// Flagging with a selection open flags the whole rectangle and closes
+    /// the selection.
+    fn apply(self, game: &mut Game) {
+        let (x, y) = (game.cursor_x, game.cursor_y);
+        match (self, game.selection) {
+            (Action::Reveal, _) => Move::Reveal(x, y).apply(game),
+            (Action::Flag, Some(anchor)) => {
+                for (cx, cy) in game.hidden_in_rect(anchor, (x, y)) {
+                    Move::Flag(cx, cy).apply(game);
+                }
+                game.selection = None;
+            }
+            (Action::Flag, None) => Move::Flag(x, y).apply(game),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Action::Reveal => "翻开",
+            Action::Flag => "插旗",
+        }
+    }
+}
+
+/// Which way the status bar clock runs. Only the number shown changes; the
+/// game is timed the same either way.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TimerMode {
+    /// Seconds taken so far.
+    Up,
+    /// Seconds left to beat the best time at this difficulty. Shown as `Up`
+    /// while there is no best time to count down to.
+    Down,
+}
+
+impl TimerMode {
+    fn other(self) -> Self {
+        match self {
+            TimerMode::Up => TimerMode::Down,
+            TimerMode::Down => TimerMode::Up,
+        }
+    }
+
+    /// The clock text for `elapsed`, counting down to `target` if there is
+    /// one and never below zero.
+    fn format(self, elapsed: Duration, target: Option<f64>) -> String {
+        match (self, target) {
+            (TimerMode::Down, Some(target)) => {
+                let left = (target - elapsed.as_secs_f64()).max(0.0).ceil();
+                format!("距纪录: {}", format_time(left as u64))
+            }
+            _ => format!("时间: {}", format_time(elapsed.as_secs())),
+        }
+    }
+}
+
+/// Whole seconds on the status bar clock as `mm:ss`, or `h:mm:ss` from an
+/// hour on. Finished games keep their hundredths elsewhere.
+fn format_time(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Whether `code` moves the cursor one cell, the keys `--repeat-ms` repeats.
+fn moves_cursor(code: KeyCode, keys: &KeyBindings) -> bool {
+    match code {
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => true,
+        KeyCode::Char(c) => keys.arrow(c).is_some(),
+        _ => false,
+    }
+}
+
+/// Applies a key press to `game`. Keys that affect more than the board are
+/// returned as a `Command` for the main loop to carry out.
+fn handle_key(game: &mut Game, key: KeyEvent, primary: Action, keys: &KeyBindings) -> Option<Command> {
+    let finished = game.game_over || game.victory;
+    let code = match key.code {
+        KeyCode::Char(c) => keys.arrow(c).unwrap_or(key.code),
+        code => code,
+    };
+    match code {
+        // Raw mode delivers Ctrl+C as a key press rather than SIGINT, so it
+        // is handled here and the normal teardown still runs.
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return Some(Command::Quit)
+        }
+        KeyCode::Char(c) if c == keys.quit => return Some(Command::Quit),
+        KeyCode::Char(c) if c == keys.restart => return Some(Command::Restart),
+        KeyCode::Char(c @ '1'..='3') if finished => {
+            return Some(Command::Difficulty(c as usize - '1' as usize))
+        }
+        KeyCode::Char(c) if c == keys.menu && finished => return Some(Command::Menu),
+        // During play the menu key does what Tab does, which a split game
+        // takes for switching boards.
+        KeyCode::Char(c) if c == keys.menu => return Some(Command::TogglePrimary),
+        // Mid-game too, to try the same layout again from the start.
+        KeyCode::Char(c) if c == keys.retry => return Some(Command::Retry),
+        KeyCode::F(2) => return Some(Command::ToggleSafeLeft),
+        KeyCode::F(3) => return Some(Command::TogglePerf),
+        KeyCode::F(4) => return Some(Command::ToggleCursor),
+        KeyCode::F(5) => return Some(Command::ToggleHeat),
+        KeyCode::F(6) => return Some(Command::TogglePlanning),
+        KeyCode::F(7) if finished => return Some(Command::Review),
+        KeyCode::F(8) => return Some(Command::ToggleRegions),
+        KeyCode::Char(c) if c == keys.odds => return Some(Command::ToggleOdds),
+        KeyCode::F(10) => return Some(Command::ToggleTimerMode),
+        KeyCode::F(11) => return Some(Command::ToggleDimSatisfied),
+        KeyCode::Char(c) if c == keys.pause && !finished => return Some(Command::Pause),
+        KeyCode::Char(c) if c == keys.auto_flag && !finished => return Some(Command::AutoFlag),
+        KeyCode::Char(c) if c == keys.hint() && !finished => return Some(Command::Hint),
+        KeyCode::Char(c) if c == keys.save && !finished => return Some(Command::Save),
+        KeyCode::Char(c) if c == keys.stats => return Some(Command::Stats),
+        KeyCode::Char(c) if c == keys.leaderboard => return Some(Command::Leaderboard),
+        KeyCode::Char(c) if c == keys.new_board => return Some(Command::NewBoard),
+        KeyCode::Char(c) if c == keys.help => return Some(Command::Help),
+        KeyCode::F(12) if !finished => Move::SafeReveal(game.cursor_x, game.cursor_y).apply(game),
+        // Only when stuck: with a provably safe cell around it does nothing.
+        KeyCode::F(9) if !finished => {
+            if let Some((x, y)) = game.safest_guess() {
+                game.assisted_guesses += 1;
+                game.cursor_x = x;
+                game.cursor_y = y;
+                Move::Reveal(x, y).apply(game);
+            }
+        }
+        KeyCode::Tab => return Some(Command::TogglePrimary),
+        KeyCode::Char(c) if c == keys.select && !finished => {
+            game.selection = match game.selection {
+                Some(_) => None,
+                None => Some((game.cursor_x, game.cursor_y)),
+            };
+        }
+        KeyCode::Esc if game.selection.is_some() => game.selection = None,
+        // With nothing to cancel, Esc is one more way out.
+        KeyCode::Esc => return Some(Command::Quit),
+        KeyCode::Enter => {
+            game.auto_complete();
+        }
+        KeyCode::Char(c) if c == keys.frontier && !finished => {
+            if let Some((x, y)) = game.next_frontier_cell((game.cursor_x, game.cursor_y)) {
+                game.cursor_x = x;
+                game.cursor_y = y;
+            }
+        }
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right if keys.wrap => {
+            let (width, height) = (game.width, game.height);
+            match code {
+                KeyCode::Up => game.cursor_y = (game.cursor_y + height - 1) % height,
+                KeyCode::Down => game.cursor_y = (game.cursor_y + 1) % height,
+                KeyCode::Left => game.cursor_x = (game.cursor_x + width - 1) % width,
+                _ => game.cursor_x = (game.cursor_x + 1) % width,
+            }
+        }
+        KeyCode::Up if game.cursor_y > 0 => game.cursor_y -= 1,
+        KeyCode::Down if game.cursor_y < game.height - 1 => game.cursor_y += 1,
+        KeyCode::Left if game.cursor_x > 0 => game.cursor_x -= 1,
+        KeyCode::Right if game.cursor_x < game.width - 1 => game.cursor_x += 1,
+        // Jumps to the board's edges; the viewport follows as with the arrows.
+        KeyCode::Home => game.cursor_x = 0,
+        KeyCode::End => game.cursor_x = game.width - 1,
+        KeyCode::PageUp => game.cursor_y = 0,
+        KeyCode::PageDown => game.cursor_y = game.height - 1,
+        KeyCode::Char(c) if c == keys.flag && !finished => primary.other().apply(game),
+        // Opens the rest of a number's neighbors once its mines are flagged;
+        // a wrong flag means one of them is a mine, and that loses as usual.
+        KeyCode::Char(c) if c == keys.chord && !finished => Move::Chord(game.cursor_x, game.cursor_y).apply(game),
+        KeyCode::Char(c) if c == keys.undo && !finished => {
+            if let Some((x, y, _)) = game.last_flag {
+                Move::UndoFlag(x, y).apply(game);
+            }
+        }
+        KeyCode::Char(c) if c == keys.reveal && !finished => primary.apply(game),
+        _ => {}
+    }
+    None
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = Options::from_env().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        eprint!("{}", USAGE);
+        process::exit(2);
+    });
+
+    let (config, mut warnings) = Config::load();
+    let difficulties = config.difficulties(builtin_difficulties(), options.max_cells, &mut warnings);
+    if options.difficulty.is_some_and(|choice| choice >= difficulties.len()) {
+        eprintln!("--difficulty 最大为 {}", difficulties.len());
+        process::exit(2);
+    }
+    let end_screen = config.end_screen;
+    let keys = config.keys.apply(options.keys, &mut warnings);
+    // Flags on the command line win over the saved settings for this run,
+    // without becoming part of them.
+    let overrides = Overrides { sound: options.sound, gentle: options.gentle, strict_flags: options.strict_flags };
+    let mut settings = config.settings;
+    let loaded_settings = settings;
+    theme::choose(settings.palette);
+    theme::choose_theme(options.theme.unwrap_or(settings.theme));
+    for warning in &warnings {
+        eprintln!("警告: {}", warning);
+    }
+    // `--repeat-ms` needs to hear when a key comes up. Terminals that can't
+    // say so keep their own key repeat, as without the option.
+    let repeat = options.repeat.filter(|_| supports_keyboard_enhancement().unwrap_or(false));
+    // A panic would otherwise leave the terminal in raw mode on the
+    // alternate screen, with its message drawn over the board.
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        if repeat.is_some() {
+            let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, Show, DisableFocusChange);
+        report(info);
+    }));
+
+    // A resumed game is a regular one, whatever else was asked for.
+    let resumed = options.resume.then(|| match &options.resume_from {
+        Some(path) => SavedGame::load_from(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        }),
+        None => SavedGame::load().unwrap_or_else(|| {
+            eprintln!("没有可以继续的自动存档");
+            process::exit(2);
+        }),
+    });
+
+    let mut scores = Scores::load();
+    let daily = (options.daily && resumed.is_none()).then(|| {
+        let date = utc_date_today();
+        let practice = scores.daily.contains_key(&date);
+        Daily { date, practice }
+    });
+    // A board from `--code` is replayed on restart until another
+    // difficulty is picked.
+    let shared = options.code.filter(|_| resumed.is_none());
+    // A custom board from `--width`/`--height`/`--mines` is kept the same
+    // way, and doesn't count towards any difficulty's best time.
+    let mut custom = options.custom.filter(|_| resumed.is_none());
+    let session = Session { options, difficulties, keys, end_screen, overrides, repeat };
+    let Session { options, difficulties, repeat, .. } = &session;
+
+    // Shared and custom boards bring their own size, so the menu is skipped;
+    // the index only picks which best time is shown.
+    let difficulty = match (&resumed, &daily, &shared) {
+        (Some(saved), _, _) => saved.difficulty.min(LABELS.len() - 1),
+        (None, Some(_), _) => DAILY_DIFFICULTY,
+        (None, None, Some(_)) => 0,
+        (None, None, None) if custom.is_some() => 0,
+        (None, None, None) => match options.difficulty {
+            Some(choice) => {
+                let (choice, board) = menu_board(MenuChoice::Difficulty(choice), difficulties);
+                custom = board;
+                choice
+            }
+            None if options.quiet => read_difficulty_quietly()?,
+            None => match select_difficulty(difficulties, &mut settings, None)? {
+                Some(choice) => {
+                    let (choice, board) = menu_board(choice, difficulties);
+                    custom = board;
+                    choice
+                }
+                None => {
+                    keep_settings(&settings, &loaded_settings);
+                    return Ok(());
+                }
+            },
+        },
+    };
+    // The saved board keeps its own size; the rules come from this run.
+    let resumed = resumed.map(|saved| {
+        session.new_game(difficulty, &daily, &shared, &custom, &settings)
+            .and_then(|game| saved.restore(game.config))
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(2);
+            })
+    });
+    // A saved board not of its difficulty's size was a custom one, and
+    // still is: it keeps out of that difficulty's times and tallies.
+    if let Some(game) = &resumed {
+        let size = (game.width, game.height, game.mines);
+        if difficulties.get(difficulty).map(Difficulty::size) != Some(size) {
+            custom = Some(size);
+        }
+    }
+
+    if let Some(path) = &options.log {
+        // Only a message: the game runs the same without its log.
+        if let Err(err) = event_log::open(path) {
+            eprintln!("警告: 无法打开日志 {}: {}", path.display(), err);
+        }
+    }
+
+    // A loaded grid keeps its mines; the rules come from this run.
+    let loaded = options.board.as_ref().map(|board| {
+        let mines: Vec<_> = board.iter_cells().filter(|(_, _, cell)| cell.is_mine).map(|(x, y, _)| (x, y)).collect();
+        session.new_game(difficulty, &daily, &shared, &custom, &settings)
+            .and_then(|game| Game::with_mines_in(game.config, &mines))
+            .unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(2);
+            })
+    });
+    let game = match resumed.or(loaded) {
+        Some(game) => game,
+        None => session.new_game(difficulty, &daily, &shared, &custom, &settings).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        }),
+    };
+    // `--split` plays a second board of the same difficulty beside it.
+    let mut boards = vec![game];
+    if options.split {
+        boards.push(session.new_game(difficulty, &daily, &shared, &custom, &settings).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(2);
+        }));
+    }
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, Hide, EnableFocusChange)?;
+    if repeat.is_some() {
+        execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+    let deal = Deal { difficulty, daily, shared, custom };
+    let (game, difficulty) = play(&mut terminal, &session, &mut settings, &mut scores, boards, deal)?;
+
+    disable_raw_mode()?;
+    if repeat.is_some() {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show, DisableFocusChange)?;
+    if options.title {
+        // Terminals without a title stack at least drop the game's title.
+        execute!(terminal.backend_mut(), SetTitle(""))?;
+        write!(terminal.backend_mut(), "\x1b[23;0t")?;
+        Write::flush(terminal.backend_mut())?;
+    }
+    if let Some(path) = &options.export_image {
+        if let Err(err) = fs::write(path, export::board_svg(&game)) {
+            eprintln!("无法导出棋盘到 {}: {}", path.display(), err);
+        }
+    }
+    if let Some(path) = &options.record {
+        if let Some(recording) = Recording::of(&game) {
+            if let Err(err) = fs::write(path, recording.to_json() + "\n") {
+                eprintln!("无法写入录像到 {}: {}", path.display(), err);
+            }
+        }
+    }
+    if let Some(path) = &options.dump_state {
+        let json = StateDump::of(&game, difficulty).to_json();
+        if path.as_os_str() == "-" {
+            println!("{}", json);
+        } else if let Err(err) = fs::write(path, json + "\n") {
+            eprintln!("无法写入游戏状态到 {}: {}", path.display(), err);
+        }
+    }
+    if let Some(path) = &options.dump {
+        let grid = game.to_ascii();
+        if path.as_os_str() == "-" {
+            print!("{}", grid);
+        } else if let Err(err) = fs::write(path, grid) {
+            eprintln!("无法写入棋盘到 {}: {}", path.display(), err);
+        }
+    }
+    keep_settings(&settings, &loaded_settings);
+    Ok(())
+}
+
+/// The command line and what `config.toml` made of it: fixed for the run,
+/// and shared by the menu and every game played.
+struct Session {
+    options: Options,
+    difficulties: Vec<Difficulty>,
+    keys: KeyBindings,
+    end_screen: EndScreen,
+    overrides: Overrides,
+    /// `--repeat-ms`, if the terminal can say when a key comes up.
+    repeat: Option<Duration>,
+}
+
+impl Session {
+    /// A new board of `difficulty`, or of the daily, shared or custom board
+    /// that replaces it, under this run's rules and `settings`.
+    fn new_game(
+        &self,
+        difficulty: usize,
+        daily: &Option<Daily>,
+        shared: &Option<BoardCode>,
+        custom: &Option<(usize, usize, usize)>,
+        settings: &Settings,
+    ) -> Result<Game, String> {
+        let Session { options, difficulties, overrides, .. } = self;
+        let settings = overrides.over(settings);
+        // The daily board ignores overrides so that everyone gets the same one.
+        let (width, height, mines) = match (daily, custom) {
+            (Some(_), _) => DIFFICULTIES[difficulty],
+            (None, Some(size)) => *size,
+            (None, None) => difficulties[difficulty].size(),
+        };
+        let mut config = GameConfig::default()
+            .size(width, height)
+            .mines(mines)
+            .reveal_budget(options.reveal_budget)
+            .max_cells(options.max_cells)
+            .strict_flag_limit(settings.strict_flags)
+            .symmetry(options.symmetry)
+            .big_opening(options.big_opening)
+            .safe_edge(options.assist)
+            .auto_first(options.auto_first)
+            .first_cell(options.first_cell)
+            .safe_reveals(options.safe_reveals)
+            .clustering(options.clustering)
+            .topology(if options.hex { Topology::Hex } else { Topology::Square })
+            .gentle(settings.gentle)
+            .no_guess(options.no_guess)
+            .auto_complete(options.auto_complete)
+            .win_condition(if options.win_by_flags { WinCondition::FlagMines } else { WinCondition::RevealSafe })
+            .mine_trigger(match (options.practice, options.lives) {
+                (true, _) => MineTrigger::Practice,
+                (false, Some(lives)) => MineTrigger::Forgiving { lives },
+                (false, None) => MineTrigger::Lose,
+            });
+        if options.zen {
+            config = config.zen();
+        }
+        if options.no_mines {
+            config = config.mines(0).zen();
+        }
+        if let Some(seed) = options.seed {
+            config = config.seed(seed);
+        }
+        if let Some(daily) = daily {
+            config = config.seed(daily_seed(&daily.date));
+        }
+        if let Some(code) = shared {
+            config = config
+                .size(code.width, code.height)
+                .mines(code.mines)
+                .seed(code.seed)
+                .symmetry(code.symmetry)
+                .big_opening(code.big_opening)
+                .safe_edge(code.safe_edge)
+                .clustering(code.clustering)
+                .topology(code.topology);
+        }
+        Game::from_config(config)
+    }
+}
+
+/// Which boards a game deals: those of `difficulty`, unless a daily,
+/// shared or custom board takes its place.
+struct Deal {
+    difficulty: usize,
+    daily: Option<Daily>,
+    shared: Option<BoardCode>,
+    custom: Option<(usize, usize, usize)>,
+}
+
+/// Plays `boards` on `terminal`, and the games after them, until the player
+/// quits. Returns the board in play at the end and its difficulty, for
+/// what the command line asks to have written out.
+fn play(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    session: &Session,
+    settings: &mut Settings,
+    scores: &mut Scores,
+    mut boards: Vec<Game>,
+    deal: Deal,
+) -> io::Result<(Game, usize)> {
+    let Session { options, difficulties, keys, end_screen, overrides, repeat } = session;
+    let Deal { mut difficulty, mut daily, mut shared, mut custom } = deal;
+    let split = boards.len() > 1;
+    // The board that takes the keys.
+    let mut active = 0;
+    // The other boards of a single-board game, oldest first, and the number
+    // of the one in play; Tab goes through them in turn.
+    let mut parked: VecDeque<Parked> = VecDeque::new();
+    let mut board_number = 1;
+    for board in &boards {
+        event_log::board(board);
+    }
+    // Daily and shared boards are fixed, so they don't adapt.
+    let mut adaptive =
+        (options.adaptive && daily.is_none() && shared.is_none()).then(|| Adaptive::new(boards[0].mines));
+    // Whether the current game's result has been recorded, so a game is
+    // counted once even though the loop keeps running on the end screen.
+    let mut recorded = false;
+    // Set when the last recorded win beat the previous best.
+    let mut new_record = false;
+    // A board played again with its seed known is not a fair time.
+    let mut replay = false;
+    let mut autoplayer: Option<Box<dyn Strategy>> =
+        options.strategy.as_deref().and_then(strategy::by_name);
+    let mut last_autoplay = Instant::now();
+    let mut perf = PerfStats::new(options.debug_perf);
+    let mut show_safe_left = true;
+    let mut primary_action = Action::Reveal;
+    // Only changes how the cursor cell is drawn; it still moves as usual.
+    let mut show_cursor = !options.no_cursor_highlight;
+    let mut show_heat = options.heat;
+    let mut dim_satisfied = !options.no_dim_satisfied;
+    let mut show_odds = false;
+    let mut show_planning = false;
+    let mut show_regions = false;
+    let mut timer_display = TimerMode::Up;
+    // Set by the pause key. Unlike a pause for lost focus, it hides the
+    // board and only that key or quitting ends it.
+    let mut pause_screen = false;
+    // Set by the quit key in a game under way; the next key answers it.
+    let mut confirming_quit = false;
+    // Set by the stats key; like a pause, the board is hidden until the next key.
+    let mut stats_screen = false;
+    // Set by the help key: the list of keys covers the board until the next
+    // key, with the clock stopped.
+    let mut help_screen = false;
+    // The difficulty shown and the first entry on screen, while the
+    // leaderboard key's screen is open.
+    let mut leaderboard: Option<(usize, usize)> = None;
+    // The name being typed for a win that made the leaderboard.
+    let mut name_entry: Option<String> = None;
+    // The active board's mines coming into view after a loss.
+    let mut explosion: Option<Explosion> = None;
+    // The border color of the last `--bell` cue, and when it was given.
+    let mut flash: Option<(Color, Instant)> = None;
+    // What the last `AutoFlag` and similar keys did, and when.
+    let mut notice: Option<(String, Instant)> = None;
+    // Set while stepping through a finished game; the board drawn is its.
+    let mut review: Option<Review> = None;
+    // When a `--replay` started playing, moved along when stepping by hand.
+    let mut playback = None;
+    if let Some(recording) = &options.playback {
+        recording.attach(&mut boards[0]);
+        review = Review::from_start(&boards[0]);
+        playback = Some(Instant::now());
+    }
+    let mut viewports: Vec<Viewport> = boards.iter().map(|_| Viewport::default()).collect();
+    let mut sound = overrides.over(settings).sound.then(Sound::new);
+    // The title last set and when.
+    let mut title: Option<(String, Instant)> = None;
+    if options.title {
+        // Saves the terminal's own title (xterm's title stack) for the exit.
+        write!(terminal.backend_mut(), "\x1b[22;0t")?;
+    }
+    let mut autosave = Autosaver::new();
+    // The movement key down under `--repeat-ms`, and when it moves next.
+    let mut held: Option<(KeyEvent, Instant)> = None;
+
+    loop {
+        for board in &mut boards {
+            board.process_pending(board.config.reveal_budget);
+        }
+        if let (Some(current), Some(started)) = (review.as_mut(), playback) {
+            current.play_to(&boards[active], started.elapsed());
+        }
+        // Losing either board of a split game loses both.
+        if boards.iter().any(|board| board.game_over) {
+            boards.iter_mut().for_each(Game::abandon);
+        }
+        let game = &mut boards[active];
+        if let Some(player) = autoplayer.as_mut() {
+            let settled = game.pending.is_empty() && !game.game_over && !game.victory && !pause_screen;
+            if settled && last_autoplay.elapsed() >= AUTOPLAY_INTERVAL {
+                last_autoplay = Instant::now();
+                if let Some(next) = player.next_move(game) {
+                    next.apply(game);
+                    autosave.touch();
+                }
+            }
+        }
+        // Games played by a strategy are not the player's own times, a
+        // shared, seeded or retried board may have been seen before, custom
+        // and adaptive boards don't have the difficulty's size, a practice
+        // board finished with mistakes is not a win, zen games aren't
+        // timed, and a split game is two boards at once. The rest make the
+        // board easier than the one the times were set on: `--assist` gives
+        // a whole side away, spare lives and `--safe-reveals` forgive
+        // mines, `--gentle`, `--big-opening` and `--no-guess` open more or
+        // guess less, `--clustering` changes how mines lie, and
+        // `--win-by-flags` ends the board before its last safe cells.
+        let counts = autoplayer.is_none()
+            && !split
+            && shared.is_none()
+            && custom.is_none()
+            && options.seed.is_none()
+            && adaptive.is_none()
+            && !replay
+            && !options.assist
+            && !matches!(game.config.mine_trigger, MineTrigger::Forgiving { .. })
+            && options.safe_reveals == 0
+            && !game.config.gentle
+            && !options.big_opening
+            && !options.no_guess
+            && options.clustering == 0.0
+            && !options.win_by_flags
+            && !game.practice_completion()
+            && game.config.timer;
+        if (game.victory || game.game_over) && !recorded && counts {
+            recorded = true;
+            scores.tallies[difficulty].record(game.victory);
+            if game.victory {
+                new_record = scores.best.update(difficulty, game.final_time(), utc_date_today());
+                if scores.qualifies(difficulty, game.final_time()) {
+                    name_entry = Some(String::new());
+                }
+                if let Some(daily) = daily.as_mut().filter(|d| !d.practice) {
+                    scores.daily.insert(daily.date.clone(), game.final_time());
+                    daily.practice = true;
+                }
+            }
+            let _ = scores.save();
+        }
+        // A save holds one board.
+        if !split {
+            autosave.tick(game, difficulty);
+        }
+        let round = Round::of(&boards);
+        let mut events = Vec::new();
+        for (index, board) in boards.iter_mut().enumerate() {
+            events.extend(board.drain_events().into_iter().map(|event| (index, event)));
+        }
+        if let Some(sound) = &sound {
+            // One tone per frame: a mine if one was hit, otherwise the first
+            // cell opened, which a cascade follows with more of the same.
+            let tone = events.iter().find_map(|(_, event)| match event {
+                GameEvent::MineHit(..) => Some(Tone::mine()),
+                _ => None,
+            });
+            let tone = tone.or_else(|| {
+                events.iter().find_map(|&(index, event)| match event {
+                    GameEvent::Revealed(x, y) => Some(Tone::for_count(boards[index].get(x, y).neighbor_mines)),
+                    _ => None,
+                })
+            });
+            if let Some(tone) = tone {
+                sound.play(tone);
+            }
+        }
+        for (index, event) in events {
+            let board = &boards[index];
+            match event {
+                GameEvent::GameOver => {
+                    event_log::event(format_args!("game over, mines hit: {}", board.exploded.len()));
+                    if index == active {
+                        explosion = Explosion::of(board);
+                    }
+                }
+                GameEvent::Victory => event_log::event(format_args!("victory in {:.2} s", board.final_time())),
+                _ => {}
+            }
+            // Each cue is one event, so it is given once however long the
+            // end screen stays up. `--sound` already sounds a mine.
+            if options.bell {
+                match event {
+                    GameEvent::MineHit(..) => {
+                        if sound.is_none() {
+                            sound::bell();
+                        }
+                        flash = Some((Color::Red, Instant::now()));
+                    }
+                    GameEvent::Victory => flash = Some((Color::Green, Instant::now())),
+                    _ => {}
+                }
+            }
+        }
+
+        if options.title {
+            let text = window_title(&boards[active], &round);
+            let due = title.as_ref().is_none_or(|(_, at)| at.elapsed() >= TITLE_INTERVAL);
+            let ended = round.over();
+            if title.as_ref().is_none_or(|(last, _)| *last != text) && (due || ended) {
+                execute!(terminal.backend_mut(), SetTitle(&text))?;
+                title = Some((text, Instant::now()));
+            }
+        }
+
+        if explosion.as_mut().is_some_and(|explosion| !explosion.tick()) {
+            explosion = None;
+        }
+
+        let draw_start = Instant::now();
+        terminal.draw(|f| {
+            let game = review.as_ref().map_or(&boards[active], |review| &review.board);
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3 + options.analyze as u16 + show_regions as u16),
+                    Constraint::Min(0),
+                ])
+                .split(f.size());
+            // Without room for the board's frame and one row inside it there
+            // is nothing to scroll; say so until the terminal grows again.
+            if layout[1].height < 3 || layout[1].width < 4 {
+                let text = Paragraph::new("终端太小，请放大窗口").wrap(Wrap { trim: true });
+                f.render_widget(text, f.size());
+                return;
+            }
+
+            let mode_text = match (&daily, &custom) {
+                (Some(daily), _) if daily.practice => format!("每日挑战 — {} (已完成, 练习)", daily.date),
+                (Some(daily), _) => format!("每日挑战 — {}", daily.date),
+                (None, Some(size)) => match difficulties[LABELS.len()..].iter().find(|added| added.size() == *size) {
+                    Some(added) => format!("难度: {} ({}x{}, {} 雷)", added.name, size.0, size.1, size.2),
+                    None => format!("自定义: {}x{}, {} 雷", size.0, size.1, size.2),
+                },
+                (None, None) => format!("难度: {}", difficulties[difficulty].name),
+            };
+            let theme = theme::current();
+            let status_style = Style::default().fg(theme.status);
+            let flash = flash.filter(|(_, at)| at.elapsed() < BELL_FLASH).map(|(color, _)| color);
+            let border_style = Style::default().fg(flash.unwrap_or(theme.border));
+            let flag_style = match game.flag_limit_hit {
+                Some(at) if at.elapsed() < FLAG_LIMIT_FLASH => {
+                    Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD)
+                }
+                _ => status_style,
+            };
+            let clock = match &review {
+                Some(review) => format!(
+                    "复盘: 第 {}/{} 步 @ {:.2} 秒  ",
+                    review.step,
+                    review.total,
+                    review.time().as_secs_f64()
+                ),
+                None if !game.config.timer => "禅模式  ".to_string(),
+                None => {
+                    let best = scores.best.records[difficulty].as_ref().map(|best| best.time);
+                    let paused = if game.paused_at.is_some() { " (已暂停)" } else { "" };
+                    format!("{}{}  ", timer_display.format(round.elapsed, best), paused)
+                }
+            };
+            let mut status_lines = vec![Spans::from(vec![
+                Span::styled(clock, status_style),
+                Span::styled(
+                    format!("剩余地雷: {} (旗帜 {}/{})", game.remaining_mines(), game.flags, game.mines),
+                    flag_style,
+                ),
+                Span::styled(format!("  {}", mode_text), status_style),
+                Span::styled(format!("  模式: {}", primary_action.label()), status_style),
+            ])];
+            if options.seed.is_some() {
+                status_lines[0].0.push(Span::styled(format!("  种子: {}", game.seed), status_style));
+            }
+            // Counting down already shows the gap to the best time.
+            let shown = counts && review.is_none() && timer_display == TimerMode::Up;
+            if let Some(best) = scores.best.records[difficulty].as_ref().filter(|_| shown) {
+                status_lines[0].0.push(Span::styled(format!("  最佳: {:.2} 秒", best.time), status_style));
+            }
+            match game.config.mine_trigger {
+                MineTrigger::Forgiving { .. } => {
+                    status_lines[0].0.push(Span::styled(format!("  生命: {}", game.lives), status_style));
+                }
+                MineTrigger::Practice => {
+                    status_lines[0].0.push(Span::styled(format!("  失误: {}", game.exploded.len()), status_style));
+                }
+                MineTrigger::Lose | MineTrigger::Zen => {}
+            }
+            if game.config.safe_reveals > 0 {
+                status_lines[0].0.push(Span::styled(format!("  安全翻开: {}", game.safe_reveals), status_style));
+            }
+            if game.assisted_guesses > 0 {
+                status_lines[0].0.push(Span::styled(
+                    format!("  辅助猜测: {}", game.assisted_guesses),
+                    status_style,
+                ));
+            }
+            if game.hints_used > 0 {
+                status_lines[0].0.push(Span::styled(format!("  提示: {}", game.hints_used), status_style));
+            }
+            // Mines can still move on the first reveal.
+            if game.start_time.is_some() {
+                let text = if options.min_moves {
+                    format!("  步数: {} / 3BV {}", game.clicks, game.calculate_3bv())
+                } else {
+                    format!("  3BV: {}", game.calculate_3bv())
+                };
+                status_lines[0].0.push(Span::styled(text, status_style));
+            }
+            if show_safe_left {
+                status_lines[0].0.push(Span::styled(
+                    format!("  剩余安全格: {}", game.safe_left()),
+                    status_style,
+                ));
+            }
+            if let Some((text, color)) = cursor_number_status(game).filter(|_| review.is_none() && !round.over()) {
+                let style = color.map_or(status_style, |color| Style::default().fg(theme::resolve(color)));
+                status_lines[0].0.push(Span::styled(format!("  {}", text), style));
+            }
+            // A flood fill larger than `--reveal-budget` spreads over several
+            // frames; say so, so the board doesn't look stuck.
+            if !game.pending.is_empty() {
+                status_lines[0].0.push(Span::styled(
+                    format!("  展开中: {} 格", game.pending.len()),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            if let Some((text, _)) = notice.as_ref().filter(|(_, at)| at.elapsed() < NOTICE_DURATION) {
+                status_lines[0].0.push(Span::styled(format!("  {}", text), Style::default().fg(Color::Yellow)));
+            }
+            let report = game.generation.filter(|r| {
+                options.verbose || r.finished_at.elapsed() < GENERATION_REPORT_DURATION
+            });
+            if let Some(report) = report {
+                let outcome = if report.solvable { "" } else { ", 未找到无猜棋盘" };
+                status_lines[0].0.push(Span::styled(
+                    format!(
+                        "  生成: {:.0} ms, 重试 {} 次{}",
+                        report.elapsed.as_secs_f64() * 1000.0,
+                        report.rejected,
+                        outcome
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            if options.analyze {
+                let traps = game.fifty_fifty_traps();
+                let listed: Vec<String> = traps
+                    .iter()
+                    .map(|((ax, ay), (bx, by))| format!("({},{})-({},{})", ax + 1, ay + 1, bx + 1, by + 1))
+                    .collect();
+                status_lines.push(Spans::from(Span::styled(
+                    format!(
+                        "最大开口: {} 格  50/50 陷阱: {} 处 {}",
+                        game.largest_opening(),
+                        traps.len(),
+                        listed.join(" ")
+                    ),
+                    Style::default().fg(if traps.is_empty() { Color::Green } else { Color::Yellow }),
+                )));
+            }
+            if show_regions {
+                let regions: Vec<String> = game
+                    .region_mine_bounds()
+                    .iter()
+                    .map(|region| {
+                        let (x, y) = region.first;
+                        let range = if region.min == region.max {
+                            region.min.to_string()
+                        } else {
+                            format!("{}~{}", region.min, region.max)
+                        };
+                        format!("({},{}) {}格 {}雷", x + 1, y + 1, region.cells, range)
+                    })
+                    .collect();
+                status_lines.push(Spans::from(Span::styled(
+                    format!("区域: {}", if regions.is_empty() { "无".to_string() } else { regions.join("  ") }),
+                    Style::default().fg(Color::Magenta),
+                )));
+            }
+            let status = Paragraph::new(status_lines).block(Block::default().borders(Borders::ALL).border_style(border_style));
+            // The safe cells left as a bar, with the counter it goes with.
+            let progress = if show_safe_left && layout[0].width >= PROGRESS_MIN_WIDTH { PROGRESS_WIDTH } else { 0 };
+            let top = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(progress)])
+                .split(layout[0]);
+            f.render_widget(status, top[0]);
+            if progress > 0 {
+                let safe = game.safe_cells();
+                let ratio = if safe == 0 { 0.0 } else { (game.revealed_safe as f64 / safe as f64).min(1.0) };
+                let gauge = Gauge::default()
+                    .block(Block::default().borders(Borders::ALL).border_style(border_style))
+                    .gauge_style(Style::default().fg(Color::Green).bg(Color::Black))
+                    .label(format!("{:.0}%", ratio * 100.0))
+                    .ratio(ratio);
+                f.render_widget(gauge, top[1]);
+            }
+
+            let look = BoardLook {
+                options,
+                cursor: show_cursor,
+                heat: show_heat,
+                planning: show_planning,
+                odds: show_odds,
+                dim_satisfied,
+                explosion: explosion.as_ref().filter(|_| review.is_none()),
+                flash,
+            };
+            if let Some((shown, scroll)) = leaderboard {
+                let title = Span::styled("排行榜", Style::default().fg(theme.title));
+                let block = Block::default().borders(Borders::ALL).border_style(border_style).title(title);
+                let mut lines = leaderboard_lines(scores, shown, scroll);
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(Span::styled("←/→ 难度  ↑/↓ 滚动  其他键 返回", Style::default().fg(Color::DarkGray))));
+                f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).block(block), layout[1]);
+            } else if stats_screen {
+                let title = Span::styled("统计", Style::default().fg(theme.title));
+                let block = Block::default().borders(Borders::ALL).border_style(border_style).title(title);
+                let mut lines = stats_lines(&scores.tallies);
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(Span::styled("按任意键返回", Style::default().fg(Color::DarkGray))));
+                f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).block(block), layout[1]);
+            } else if pause_screen {
+                // The board stays hidden, so a pause can't be used to think.
+                let title = Span::styled("扫雷", Style::default().fg(theme.title));
+                f.render_widget(Block::default().borders(Borders::ALL).border_style(border_style).title(title), layout[1]);
+                let lines = vec![
+                    Spans::from(Span::styled("已暂停", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+                    Spans::from(format!("{} 继续  {} 退出", keys.pause, keys.quit)),
+                ];
+                let width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16;
+                let area = centered_rect(width + 4, lines.len() as u16 + 2, layout[1]);
+                let panel = Paragraph::new(lines).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL));
+                f.render_widget(panel, area);
+            } else if review.is_some() || !split {
+                // The date names the board, so players can tell they share it.
+                let mut name = match &daily {
+                    Some(daily) => format!("扫雷 — 每日挑战 {}", daily.date),
+                    None => "扫雷".to_string(),
+                };
+                if !parked.is_empty() {
+                    name += &format!(" — 棋盘 {}/{}", board_number, parked.len() + 1);
+                }
+                // Until the first reveal, for whoever doesn't know the keys yet.
+                if game.start_time.is_none() && !game.game_over && !game.victory {
+                    name += &format!(" — 按 {} 查看按键", keys.help);
+                }
+                let title = Span::styled(name, Style::default().fg(theme.title));
+                draw_board(f, layout[1], title, game, &look, &mut viewports[active]);
+            } else {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(vec![Constraint::Ratio(1, boards.len() as u32); boards.len()])
+                    .split(layout[1]);
+                for (index, board) in boards.iter().enumerate() {
+                    // Only the active board shows its cursor.
+                    let (color, cursor) =
+                        if index == active { (theme.title, look.cursor) } else { (Color::DarkGray, false) };
+                    let title = Span::styled(format!("扫雷 {}", index + 1), Style::default().fg(color));
+                    let explosion = look.explosion.filter(|_| index == active);
+                    let look = BoardLook { cursor, explosion, ..look };
+                    draw_board(f, columns[index], title, board, &look, &mut viewports[index]);
+                }
+            }
+
+            if round.over() && review.is_none() && explosion.is_none() {
+                let (outcome, color) = if game.practice_completion() {
+                    (
+                        format!("练习完成! 失误 {} 次, 用时 {:.2} 秒", game.exploded.len(), game.final_time()),
+                        Color::Yellow,
+                    )
+                } else if round.won && !game.config.timer {
+                    ("完成!".to_string(), Color::Green)
+                } else if round.won {
+                    (format!("胜利! 用时 {:.2} 秒", round.final_time()), Color::Green)
+                } else {
+                    (format!("踩到地雷, 游戏结束! 用时 {:.2} 秒", round.final_time()), Color::Red)
+                };
+                let mut lines = vec![
+                    Spans::from(Span::styled(outcome, Style::default().fg(color).add_modifier(Modifier::BOLD))),
+                ];
+                if end_screen.progress {
+                    lines.push(Spans::from(format!(
+                        "安全格: {}/{}  旗帜: {}",
+                        game.revealed_safe, game.safe_cells(), game.flags
+                    )));
+                }
+                if end_screen.efficiency && game.config.timer && game.clicks > 0 {
+                    let bbbv = game.calculate_3bv();
+                    let seconds = round.final_time().max(0.01);
+                    lines.push(Spans::from(format!(
+                        "3BV: {}  3BV/s: {:.2}  点击: {}  效率: {:.0}%",
+                        bbbv,
+                        bbbv as f64 / seconds,
+                        game.clicks,
+                        bbbv as f64 / game.clicks as f64 * 100.0
+                    )));
+                }
+                // Chords can open more than one 3BV per move, so fewer is
+                // perfect too.
+                if options.min_moves && round.won && game.clicks as u32 <= game.calculate_3bv() {
+                    let banner = format!("完美! {} 步完成 (3BV {})", game.clicks, game.calculate_3bv());
+                    lines.push(Spans::from(Span::styled(banner, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+                }
+                if replay {
+                    lines.push(Spans::from("重试局不计入最佳时间"));
+                }
+                if game.assisted_guesses > 0 {
+                    lines.push(Spans::from(format!("辅助猜测 {} 次", game.assisted_guesses)));
+                }
+                // Only beside a game that could have set it, as in the status bar.
+                if let Some(best) = scores.best.records[difficulty].as_ref().filter(|_| end_screen.best && counts) {
+                    let set_on = match &best.date {
+                        Some(date) => format!("创纪录于 {}", date),
+                        None => "日期未知".to_string(),
+                    };
+                    let prefix = if new_record { "新纪录! " } else { "" };
+                    lines.push(Spans::from(format!("{}最佳: {:.2} 秒 ({})", prefix, best.time, set_on)));
+                }
+                if end_screen.code {
+                    lines.push(Spans::from(format!("棋盘代码: {}", BoardCode::of(game).encode())));
+                }
+                lines.push(Spans::from(format!(
+                    "{} 重新开始  {} 重试本局  F7 复盘  1/2/3 切换难度  {} 菜单  {} 退出",
+                    keys.restart, keys.retry, keys.menu, keys.quit
+                )));
+                // Sized from the text itself. When the play area is too
+                // narrow, the lines wrap left-aligned instead of being cut
+                // off at both ends.
+                let content_width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16;
+                let text_width = layout[1].width.saturating_sub(2).max(1);
+                let fits = content_width <= text_width;
+                let rows: u16 = lines
+                    .iter()
+                    .map(|line| (line.width() as u16).div_ceil(text_width).max(1))
+                    .sum();
+                let area = centered_rect(content_width.min(text_width) + 2, rows + 2, layout[1]);
+                let panel = Paragraph::new(lines)
+                    .alignment(if fits { Alignment::Center } else { Alignment::Left })
+                    .wrap(Wrap { trim: true })
+                    .block(Block::default().borders(Borders::ALL));
+                f.render_widget(Clear, area);
+                f.render_widget(panel, area);
+            }
+
+            if help_screen {
+                let mut lines = help_lines(keys);
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(Span::styled("按任意键返回", Style::default().fg(Color::DarkGray))));
+                let width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16;
+                let area = centered_rect(width + 4, lines.len() as u16 + 2, layout[1]);
+                let title = Span::styled("按键", Style::default().fg(theme.title));
+                let block = Block::default().borders(Borders::ALL).border_style(border_style).title(title);
+                f.render_widget(Clear, area);
+                f.render_widget(Paragraph::new(lines).alignment(Alignment::Center).block(block), area);
+            }
+
+            if let Some(name) = &name_entry {
+                let (fewest, most) = NAME_LENGTH;
+                let lines = vec![
+                    Spans::from(Span::styled("进入排行榜! 输入名字:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+                    Spans::from(Span::styled(format!(" {}_ ", name), Style::default().add_modifier(Modifier::REVERSED))),
+                    Spans::from(format!("{}-{} 个字符  Enter 保存  Esc 跳过", fewest, most)),
+                ];
+                let width = lines.iter().map(Spans::width).max().unwrap_or(0).max(most + 2) as u16;
+                let area = centered_rect(width + 4, lines.len() as u16 + 2, layout[1]);
+                let panel = Paragraph::new(lines).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL));
+                f.render_widget(Clear, area);
+                f.render_widget(panel, area);
+            }
+
+            if confirming_quit {
+                let lines = vec![
+                    Spans::from(Span::styled("退出游戏?", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+                    Spans::from("y 退出  其他键 继续"),
+                ];
+                let width = lines.iter().map(Spans::width).max().unwrap_or(0) as u16;
+                let area = centered_rect(width + 4, lines.len() as u16 + 2, layout[1]);
+                let panel = Paragraph::new(lines).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL));
+                f.render_widget(Clear, area);
+                f.render_widget(panel, area);
+            }
+
+            if perf.visible {
+                let text = format!(
+                    " {:.1} ms  {:.0} fps ",
+                    perf.draw_time.as_secs_f64() * 1000.0,
+                    perf.fps
+                );
+                let width = (text.len() as u16).min(layout[1].width.saturating_sub(2));
+                // Sits on the board block's top border, clear of the grid.
+                let area = Rect::new(
+                    layout[1].right().saturating_sub(width + 1),
+                    layout[1].y,
+                    width,
+                    1.min(layout[1].height),
+                );
+                f.render_widget(
+                    Paragraph::new(Span::styled(text, Style::default().fg(Color::DarkGray))),
+                    area,
+                );
+            }
+        })?;
+        perf.record(draw_start.elapsed());
+
+        // A held movement key comes round again on its own schedule, as if
+        // pressed once more.
+        let repeated = held.as_mut().filter(|(_, next)| Instant::now() >= *next).map(|(key, next)| {
+            *next = Instant::now() + repeat.unwrap_or(REPEAT_DELAY);
+            *key
+        });
+        let event = match repeated {
+            Some(key) => Event::Key(key),
+            None if !event::poll(Duration::from_millis(16))? => continue,
+            // Terminals that report focus pause the clock while the player
+            // is away; elsewhere these never arrive and the clock just runs.
+            None => event::read()?,
+        };
+        match event {
+            Event::FocusLost => boards.iter_mut().for_each(Game::pause),
+            Event::FocusGained if !pause_screen => boards.iter_mut().for_each(Game::resume),
+            // Cells left over from the old size would linger where the new
+            // layout draws nothing, so the next frame starts from blank.
+            Event::Resize(..) => terminal.clear()?,
+            _ => {}
+        }
+        if let Event::Key(key) = event {
+            let moving = moves_cursor(key.code, keys);
+            if repeat.is_some() && repeated.is_none() {
+                match key.kind {
+                    KeyEventKind::Press if moving => held = Some((key, Instant::now() + REPEAT_DELAY)),
+                    KeyEventKind::Press => held = None,
+                    // The terminal's own repeats would only add to ours.
+                    KeyEventKind::Repeat if moving => continue,
+                    KeyEventKind::Repeat => {}
+                    KeyEventKind::Release => {
+                        if held.is_some_and(|(down, _)| down.code == key.code) {
+                            held = None;
+                        }
+                        continue;
+                    }
+                }
+            }
+            // Skips the rest of the animation, and does nothing else.
+            if explosion.take().is_some() {
+                continue;
+            }
+            if pause_screen {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || key.code == KeyCode::Char(keys.quit) {
+                    if !split {
+                        autosave.flush(&boards[active], difficulty);
+                    }
+                    break;
+                }
+                if key.code == KeyCode::Char(keys.pause) {
+                    pause_screen = false;
+                    boards.iter_mut().for_each(Game::resume);
+                }
+                continue;
+            }
+            if let Some(name) = name_entry.as_mut() {
+                let (fewest, most) = NAME_LENGTH;
+                match key.code {
+                    KeyCode::Char(c) if !c.is_control() && name.chars().count() < most => name.push(c),
+                    KeyCode::Backspace => {
+                        name.pop();
+                    }
+                    KeyCode::Enter if name.trim().chars().count() >= fewest => {
+                        let entry = Entry {
+                            name: name.trim().to_string(),
+                            time: boards[active].final_time(),
+                            date: utc_date_today(),
+                        };
+                        if let Some(rank) = scores.add_entry(difficulty, entry) {
+                            notice = Some((format!("排行榜第 {} 名", rank + 1), Instant::now()));
+                        }
+                        let _ = scores.save();
+                        name_entry = None;
+                    }
+                    KeyCode::Esc => name_entry = None,
+                    _ => {}
+                }
+                continue;
+            }
+            if stats_screen || help_screen {
+                stats_screen = false;
+                help_screen = false;
+                boards.iter_mut().for_each(Game::resume);
+                continue;
+            }
+            if let Some((shown, scroll)) = leaderboard.as_mut() {
+                let entries = scores.leaderboard[*shown].len();
+                match key.code {
+                    KeyCode::Left => (*shown, *scroll) = ((*shown + 2) % 3, 0),
+                    KeyCode::Right => (*shown, *scroll) = ((*shown + 1) % 3, 0),
+                    KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                    KeyCode::Down => *scroll = (*scroll + 1).min(entries.saturating_sub(1)),
+                    _ => {
+                        leaderboard = None;
+                        boards.iter_mut().for_each(Game::resume);
+                    }
+                }
+                continue;
+            }
+            if confirming_quit {
+                confirming_quit = false;
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || matches!(key.code, KeyCode::Char('y' | 'Y')) {
+                    if !split {
+                        autosave.flush(&boards[active], difficulty);
+                    }
+                    break;
+                }
+                boards.iter_mut().for_each(Game::resume);
+                continue;
+            }
+            // In case the focus report back got lost.
+            boards.iter_mut().for_each(Game::resume);
+            let game = &mut boards[active];
+            // Reviewing only moves through the recorded steps; the game
+            // itself is left alone.
+            if let Some(current) = review.as_mut() {
+                match key.code {
+                    KeyCode::Left => current.seek(game, current.step.saturating_sub(1)),
+                    KeyCode::Right => current.seek(game, current.step + 1),
+                    KeyCode::Home => current.seek(game, 0),
+                    KeyCode::End => current.seek(game, current.total),
+                    // A replay has no game of its own to go back to.
+                    KeyCode::Esc | KeyCode::F(7) if playback.is_some() => break,
+                    KeyCode::Esc | KeyCode::F(7) => review = None,
+                    KeyCode::Char(c) if c == keys.quit => break,
+                    _ => {}
+                }
+                if let Some(current) = review.as_ref().filter(|_| playback.is_some()) {
+                    playback = Instant::now().checked_sub(current.time()).or(playback);
+                }
+                continue;
+            }
+            // Tab picks the other board instead of swapping the actions.
+            if split && key.code == KeyCode::Tab {
+                active = (active + 1) % boards.len();
+                continue;
+            }
+            // The board left keeps its clock stopped until it comes back.
+            if key.code == KeyCode::Tab && !parked.is_empty() {
+                let next = parked.pop_front().expect("checked above");
+                let mut current = mem::replace(&mut boards[0], next.game);
+                current.pause();
+                boards[0].resume();
+                parked.push_back(Parked {
+                    game: current,
+                    viewport: mem::replace(&mut viewports[0], next.viewport),
+                    number: mem::replace(&mut board_number, next.number),
+                    recorded: mem::replace(&mut recorded, next.recorded),
+                    new_record: mem::replace(&mut new_record, next.new_record),
+                    replay: mem::replace(&mut replay, next.replay),
+                });
+                event_log::board(&boards[0]);
+                explosion = None;
+                autosave.touch();
+                continue;
+            }
+            autosave.touch();
+            match handle_key(game, key, primary_action, keys) {
+                // Only the quit key and Esc ask, and only once there is a
+                // clock running to lose; Ctrl+C and finished games quit at once.
+                Some(Command::Quit)
+                    if (key.code == KeyCode::Char(keys.quit) || key.code == KeyCode::Esc)
+                        && game.start_time.is_some()
+                        && !round.over() =>
+                {
+                    confirming_quit = true;
+                    boards.iter_mut().for_each(Game::pause);
+                }
+                Some(Command::Quit) => {
+                    if !split {
+                        autosave.flush(game, difficulty);
+                    }
+                    break;
+                }
+                Some(Command::Restart) => {
+                    // Same difficulty, so the grid is reused as is.
+                    if let Some(adaptive) = adaptive.as_mut() {
+                        adaptive.record(game);
+                        game.config = game.config.clone().mines(adaptive.mines);
+                    }
+                    for board in &mut boards {
+                        board.reset();
+                        event_log::board(board);
+                    }
+                    autosave.discard();
+                    replay = false;
+                    recorded = false;
+                    new_record = false;
+                }
+                Some(Command::Retry) => {
+                    for board in &mut boards {
+                        board.retry();
+                        event_log::board(board);
+                    }
+                    autosave.discard();
+                    replay = true;
+                    recorded = false;
+                    new_record = false;
+                }
+                Some(Command::Difficulty(choice)) => {
+                    // Picking a difficulty leaves the daily challenge for a
+                    // regular game. One over `--max-cells` can't be played, so
+                    // the key does nothing.
+                    let next: Result<Vec<Game>, String> = boards.iter().map(|_| session.new_game(choice, &None, &None, &None, settings)).collect();
+                    let Ok(next) = next else { continue };
+                    difficulty = choice;
+                    daily = None;
+                    shared = None;
+                    custom = None;
+                    boards = next;
+                    boards.iter().for_each(event_log::board);
+                    // The boards set aside were of the old difficulty.
+                    parked.clear();
+                    board_number = 1;
+                    autosave.discard();
+                    adaptive = options.adaptive.then(|| Adaptive::new(boards[0].mines));
+                    replay = false;
+                    recorded = false;
+                    new_record = false;
+                }
+                Some(Command::TogglePerf) => perf.visible = !perf.visible,
+                Some(Command::TogglePrimary) => primary_action = primary_action.other(),
+                Some(Command::ToggleSafeLeft) => show_safe_left = !show_safe_left,
+                Some(Command::ToggleCursor) => show_cursor = !show_cursor,
+                Some(Command::ToggleHeat) => show_heat = !show_heat,
+                Some(Command::ToggleDimSatisfied) => dim_satisfied = !dim_satisfied,
+                Some(Command::TogglePlanning) => show_planning = !show_planning,
+                Some(Command::Review) => review = Review::new(game),
+                Some(Command::ToggleRegions) => show_regions = !show_regions,
+                Some(Command::ToggleOdds) => show_odds = !show_odds,
+                Some(Command::ToggleTimerMode) => timer_display = timer_display.other(),
+                Some(Command::AutoFlag) => {
+                    let text = match game.auto_flag() {
+                        0 => "没有能确定的地雷".to_string(),
+                        placed => format!("自动插旗: {} 面", placed),
+                    };
+                    notice = Some((text, Instant::now()));
+                }
+                Some(Command::Hint) => {
+                    let found = game.ask_hint();
+                    if !found {
+                        notice = Some(("没有确定安全的格子".to_string(), Instant::now()));
+                    }
+                }
+                Some(Command::Save) => {
+                    let text = match SavedGame::of(game, difficulty).save() {
+                        Ok(path) => format!("已保存到 {}", path.display()),
+                        Err(err) => err,
+                    };
+                    notice = Some((text, Instant::now()));
+                }
+                Some(Command::Stats) => {
+                    stats_screen = true;
+                    boards.iter_mut().for_each(Game::pause);
+                }
+                Some(Command::Leaderboard) => {
+                    leaderboard = Some((difficulty, 0));
+                    boards.iter_mut().for_each(Game::pause);
+                }
+                Some(Command::Help) => {
+                    help_screen = true;
+                    boards.iter_mut().for_each(Game::pause);
+                }
+                // A split game already has its two boards.
+                Some(Command::NewBoard) if split => {}
+                Some(Command::NewBoard) => {
+                    let next = match session.new_game(difficulty, &daily, &shared, &custom, settings) {
+                        Ok(next) => next,
+                        Err(err) => {
+                            notice = Some((err, Instant::now()));
+                            continue;
+                        }
+                    };
+                    let mut current = mem::replace(game, next);
+                    current.pause();
+                    parked.push_back(Parked {
+                        game: current,
+                        viewport: mem::take(&mut viewports[0]),
+                        number: mem::replace(&mut board_number, parked.len() + 2),
+                        recorded: mem::replace(&mut recorded, false),
+                        new_record: mem::replace(&mut new_record, false),
+                        replay: mem::replace(&mut replay, false),
+                    });
+                    event_log::board(&boards[0]);
+                    explosion = None;
+                    autosave.touch();
+                }
+                Some(Command::Pause) => {
+                    boards.iter_mut().for_each(Game::pause);
+                    // Before the first reveal there is no clock to stop.
+                    pause_screen = boards.iter().any(|board| board.paused_at.is_some());
+                }
+                Some(Command::Menu) => {
+                    // The menu takes every key event as a press.
+                    if repeat.is_some() {
+                        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+                    }
+                    held = None;
+                    disable_raw_mode()?;
+                    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show)?;
+                    // Quitting here skips going back to the alternate screen;
+                    // the teardown below is harmless on the normal one.
+                    let mut failed: Option<String> = None;
+                    let picked = loop {
+                        let Some(choice) = select_difficulty(difficulties, settings, failed.as_deref())? else {
+                            break None;
+                        };
+                        let (choice, board) = menu_board(choice, difficulties);
+                        match boards.iter().map(|_| session.new_game(choice, &None, &None, &board, settings)).collect() {
+                            Ok(next) => break Some((choice, board, next)),
+                            Err(err) => failed = Some(err),
+                        }
+                    };
+                    let Some((choice, board, next)) = picked else { break };
+                    difficulty = choice;
+                    let playing = overrides.over(settings);
+                    if playing.sound != sound.is_some() {
+                        sound = playing.sound.then(Sound::new);
+                    }
+                    enable_raw_mode()?;
+                    execute!(terminal.backend_mut(), EnterAlternateScreen, Hide)?;
+                    if repeat.is_some() {
+                        execute!(
+                            terminal.backend_mut(),
+                            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+                        )?;
+                    }
+                    terminal.clear()?;
+                    daily = None;
+                    shared = None;
+                    custom = board;
+                    boards = next;
+                    boards.iter().for_each(event_log::board);
+                    // The boards set aside were of the old difficulty.
+                    parked.clear();
+                    board_number = 1;
+                    autosave.discard();
+                    adaptive = options.adaptive.then(|| Adaptive::new(boards[0].mines));
+                    replay = false;
+                    recorded = false;
+                    new_record = false;
+                }
+                None => {}
+            }
+        }
+    }
+    Ok((boards.swap_remove(active), difficulty))
+}
+
+/// The settings the command line turns on for one run. They are laid over
+/// the saved settings as each game starts rather than merged into them, so
+/// the settings screen shows, and `keep_settings` writes back, only what
+/// the player chose.
+#[derive(Clone, Copy)]
+struct Overrides {
+    sound: bool,
+    gentle: bool,
+    strict_flags: bool,
+}
+
+impl Overrides {
+    /// `settings` as this run plays them.
+    fn over(self, settings: &Settings) -> Settings {
+        Settings {
+            sound: settings.sound || self.sound,
+            gentle: settings.gentle || self.gentle,
+            strict_flags: settings.strict_flags || self.strict_flags,
+            ..*settings
+        }
+    }
+}
+
+/// Writes the settings to `config.toml` if the settings screen changed
+/// them. Failing to is only a warning; the game is over anyway.
+fn keep_settings(settings: &Settings, loaded: &Settings) {
+    if settings == loaded {
+        return;
+    }
+    if let Err(err) = settings.save() {
+        eprintln!("警告: 无法保存设置: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minesweeper::{strategy::Move, CellState, Game};
+
+    use tui::{
+        backend::TestBackend,
+        style::{Color, Modifier, Style},
+        text::Span,
+        Terminal,
+    };
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{cell_style, cell_symbol, draw_board, BoardLayout, BoardLook, Options, Viewport, cursor_number_status, Overrides, format_time, handle_key, help_lines, moves_cursor, number_color, parse_difficulty, post_mortem, satisfied, theme, Action, Command, Explosion, EXPLOSION_FRAMES};
+    use crate::{
+        builtin_difficulties,
+        config::{Config, KeyOverrides, Settings},
+        keys::KeyBindings,
+        menu::MenuChoice,
+        menu_board,
+    };
+
+    #[test]
+    fn the_explosion_shows_the_nearest_mines_first() {
+        let mut game = Game::with_mines(8, 1, &[(0, 0), (3, 0), (7, 0)]).unwrap();
+        game.reveal(1, 0);
+        game.reveal(3, 0);
+        let mut explosion = Explosion::of(&game).unwrap();
+        assert!(explosion.shown(3, 0) && !explosion.shown(0, 0));
+        while !explosion.shown(0, 0) {
+            assert!(!explosion.shown(7, 0));
+            assert!(explosion.tick());
+        }
+        for _ in explosion.frame + 1..EXPLOSION_FRAMES {
+            assert!(explosion.tick());
+        }
+        assert!(explosion.shown(7, 0));
+        assert!(!explosion.tick());
+    }
+
+    #[test]
+    fn key_overrides_fall_back_when_invalid_or_shared() {
+        let defaults = KeyBindings::default();
+        let mut warnings = Vec::new();
+        let remap = KeyOverrides { flag: Some("x".into()), reveal: Some("zz".into()), ..Default::default() };
+        let keys = remap.apply(defaults, &mut warnings);
+        assert_eq!((keys.flag, keys.reveal), ('x', ' '));
+        assert_eq!(warnings.len(), 1);
+
+        let clash = KeyOverrides { flag: Some("q".into()), ..Default::default() };
+        assert_eq!(clash.apply(defaults, &mut warnings), defaults);
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn the_menu_key_switches_to_flagging_until_the_game_ends() {
+        let keys = KeyBindings::default();
+        let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let mut game = Game::with_mines(3, 1, &[(0, 0)]).unwrap();
+        assert_eq!(handle_key(&mut game, press('m'), Action::Reveal, &keys), Some(Command::TogglePrimary));
+        handle_key(&mut game, press(' '), Action::Flag, &keys);
+        assert_eq!(game.flags, 1);
+        game.game_over = true;
+        assert_eq!(handle_key(&mut game, press('m'), Action::Reveal, &keys), Some(Command::Menu));
+    }
+
+    #[test]
+    fn the_number_under_the_cursor_says_how_many_flags_it_needs() {
+        // * 1 . .
+        let mut game = Game::with_mines(4, 1, &[(0, 0)]).unwrap();
+        assert_eq!(cursor_number_status(&game), None);
+        game.reveal(1, 0);
+        game.cursor_x = 1;
+        assert_eq!(cursor_number_status(&game), Some(("还差 1 面旗".to_string(), None)));
+        Move::Flag(0, 0).apply(&mut game);
+        assert_eq!(cursor_number_status(&game), Some(("已满足".to_string(), Some(Color::Green))));
+        Move::Flag(2, 0).apply(&mut game);
+        assert_eq!(cursor_number_status(&game), Some(("多了 1 面旗".to_string(), Some(Color::Yellow))));
+    }
+
+    #[test]
+    fn only_the_arrows_and_vim_moves_repeat() {
+        let keys = KeyBindings::default();
+        assert!(moves_cursor(KeyCode::Left, &keys));
+        assert!(!moves_cursor(KeyCode::Char('h'), &keys));
+        assert!(!moves_cursor(KeyCode::Char(' '), &keys));
+        assert!(!moves_cursor(KeyCode::Home, &keys));
+        let vim = KeyBindings { vim: true, ..keys };
+        assert!(moves_cursor(KeyCode::Char('h'), &vim));
+    }
+
+    #[test]
+    fn the_help_lists_the_keys_as_they_are_bound() {
+        let text = |keys: &KeyBindings| {
+            help_lines(keys).iter().map(|line| line.0.iter().map(|span| span.content.to_string()).collect::<String>()).collect::<Vec<_>>()
+        };
+        let defaults = text(&KeyBindings::default());
+        assert!(defaults.iter().any(|line| line.starts_with("空格") && line.contains("翻开")));
+        assert!(defaults.iter().any(|line| line.starts_with("f ") && line.contains("插旗")));
+        let mut warnings = Vec::new();
+        let remap = KeyOverrides { flag: Some("x".to_string()), ..KeyOverrides::default() };
+        let remapped = text(&remap.apply(KeyBindings::default(), &mut warnings));
+        assert!(remapped.iter().any(|line| line.starts_with("x ") && line.contains("插旗")));
+        assert!(!remapped.iter().any(|line| line.starts_with("f ")));
+        // `?` is taken by the help itself.
+        let clash = KeyOverrides { chord: Some("?".to_string()), ..KeyOverrides::default() };
+        assert_eq!(clash.apply(KeyBindings::default(), &mut warnings), KeyBindings::default());
+        let question = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT);
+        let mut game = Game::new(3, 3, 1).unwrap();
+        assert_eq!(handle_key(&mut game, question, Action::Reveal, &KeyBindings::default()), Some(Command::Help));
+    }
+
+    #[test]
+    fn esc_cancels_a_selection_before_it_quits() {
+        let keys = KeyBindings::default();
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        let mut game = Game::with_mines(3, 1, &[(0, 0)]).unwrap();
+        game.selection = Some((0, 0));
+        assert_eq!(handle_key(&mut game, esc, Action::Reveal, &keys), None);
+        assert_eq!(game.selection, None);
+        assert_eq!(handle_key(&mut game, esc, Action::Reveal, &keys), Some(Command::Quit));
+    }
+
+    #[test]
+    fn shift_and_the_frontier_key_ask_for_another_board() {
+        let keys = KeyBindings::default();
+        let mut game = Game::with_mines(3, 1, &[(0, 0)]).unwrap();
+        let key = KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key(&mut game, key, Action::Reveal, &keys), Some(Command::NewBoard));
+        game.victory = true;
+        assert_eq!(handle_key(&mut game, key, Action::Reveal, &keys), Some(Command::NewBoard));
+    }
+
+    #[test]
+    fn the_clock_shows_minutes_and_then_hours() {
+        assert_eq!(format_time(0), "00:00");
+        assert_eq!(format_time(59), "00:59");
+        assert_eq!(format_time(60), "01:00");
+        assert_eq!(format_time(3599), "59:59");
+        assert_eq!(format_time(3661), "1:01:01");
+    }
+
+    #[test]
+    fn wrap_brings_the_cursor_in_at_the_opposite_edge() {
+        let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut game = Game::with_mines(3, 2, &[(0, 0)]).unwrap();
+        let clamped = KeyBindings::default();
+        handle_key(&mut game, press(KeyCode::Left), Action::Reveal, &clamped);
+        assert_eq!((game.cursor_x, game.cursor_y), (0, 0));
+
+        let keys = KeyBindings { wrap: true, ..clamped };
+        handle_key(&mut game, press(KeyCode::Left), Action::Reveal, &keys);
+        handle_key(&mut game, press(KeyCode::Up), Action::Reveal, &keys);
+        assert_eq!((game.cursor_x, game.cursor_y), (2, 1));
+        handle_key(&mut game, press(KeyCode::Right), Action::Reveal, &keys);
+        handle_key(&mut game, press(KeyCode::Down), Action::Reveal, &keys);
+        assert_eq!((game.cursor_x, game.cursor_y), (0, 0));
+    }
+
+    #[test]
+    fn menu_answers_outside_one_to_three_are_beginner() {
+        for input in ["0", "abc", "4", "", "\n", "-1", "18446744073709551616"] {
+            assert_eq!(parse_difficulty(input), 0, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn menu_answers_pick_their_difficulty() {
+        assert_eq!(parse_difficulty("1\n"), 0);
+        assert_eq!(parse_difficulty(" 2 "), 1);
+        assert_eq!(parse_difficulty("3\n"), 2);
+    }
+
+    #[test]
+    fn configured_difficulties_follow_the_built_in_three() {
+        let config: Config = toml::from_str(
+            r#"
+            [presets.beginner]
+            width = 9
+            height = 9
+            mines = 10
+
+            [[difficulties]]
+            name = "Evil"
+            width = 30
+            height = 30
+            mines = 200
+
+            [[difficulties]]
+            name = "Full"
+            width = 2
+            height = 2
+            mines = 4
+            "#,
+        )
+        .unwrap();
+        let mut warnings = Vec::new();
+        let difficulties = config.difficulties(builtin_difficulties(), 1_000_000, &mut warnings);
+        let sizes: Vec<_> = difficulties.iter().map(|d| d.size()).collect();
+        assert_eq!(sizes, [(9, 9, 10), (16, 16, 40), (24, 20, 99), (30, 30, 200)]);
+        assert_eq!(difficulties[0].name, "初级");
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(menu_board(MenuChoice::Difficulty(1), &difficulties), (1, None));
+        assert_eq!(menu_board(MenuChoice::Difficulty(3), &difficulties), (0, Some((30, 30, 200))));
+    }
+
+    #[test]
+    fn a_number_is_satisfied_once_its_flags_are_all_placed() {
+        // * 1 .
+        let mut game = Game::with_mines(3, 1, &[(0, 0)]).unwrap();
+        game.reveal(1, 0);
+        let satisfied_at = |game: &Game, x| satisfied(game, x, 0, game.get(x, 0));
+        assert!(!satisfied_at(&game, 1));
+        Move::Flag(0, 0).apply(&mut game);
+        assert!(satisfied_at(&game, 1));
+        // Hidden cells and empty ones have nothing to chord.
+        assert!(!satisfied_at(&game, 0));
+        game.reveal(2, 0);
+        assert!(!satisfied_at(&game, 2));
+    }
+
+    #[test]
+    fn a_lost_board_shows_every_mine_and_wrong_flag() {
+        // * . . .
+        // . . . .
+        // . . . *
+        let mut game = Game::with_mines(4, 3, &[(0, 0), (3, 2)]).unwrap();
+        game.reveal(3, 0);
+        Move::Flag(0, 2).apply(&mut game);
+        game.reveal(0, 0);
+        assert!(game.game_over);
+        let symbol = |x: usize, y: usize| post_mortem(&game, x, y, game.get(x, y)).map(|(symbol, _)| symbol);
+        assert_eq!(symbol(0, 0), Some('*'));
+        assert_eq!(symbol(3, 2), Some('*'));
+        assert_eq!(symbol(0, 2), Some('✗'));
+        assert_eq!(symbol(1, 0), None);
+        let detonated = post_mortem(&game, 0, 0, game.get(0, 0)).map(|(_, style)| style);
+        let other = post_mortem(&game, 3, 2, game.get(3, 2)).map(|(_, style)| style);
+        assert_ne!(detonated, other);
+    }
+
+    #[test]
+    fn command_line_settings_last_one_run_only() {
+        let overrides = Overrides { sound: true, gentle: false, strict_flags: true };
+        let saved = Settings { gentle: true, ..Settings::default() };
+        let playing = overrides.over(&saved);
+        assert!(playing.sound && playing.gentle && playing.strict_flags);
+        assert!(!saved.sound && !saved.strict_flags);
+        let quiet = Overrides { sound: false, gentle: false, strict_flags: false };
+        assert!(quiet.over(&saved) == saved);
+    }
+
+    #[test]
+    fn the_saved_theme_goes_by_its_name() {
+        let config: Config = toml::from_str("[settings]\ntheme = \"mono\"\n").unwrap();
+        assert_eq!(config.settings.theme, 3);
+        let saved = toml::to_string(&config.settings).unwrap();
+        assert!(saved.contains("theme = \"mono\""));
+        assert!(toml::from_str::<Config>("[settings]\ntheme = \"sepia\"\n").is_err());
+    }
+
+    #[test]
+    fn counts_past_nine_are_drawn_in_full() {
+        let game = Game::with_mines(2, 1, &[(0, 0)]).unwrap();
+        let mut cell = *game.get(1, 0);
+        cell.state = CellState::Revealed;
+        cell.neighbor_mines = 12;
+        assert_eq!(cell_symbol(&game, &cell), "12");
+        assert_eq!(game.render_char(&cell), '+');
+        cell.neighbor_mines = 9;
+        assert_eq!(cell_symbol(&game, &cell), "9");
+    }
+
+    #[test]
+    fn cells_are_drawn_in_their_own_colors() {
+        // The classic theme on the 16 colors, whatever this terminal says.
+        theme::choose(theme::PaletteChoice::Basic);
+        let mut game = Game::with_mines(3, 3, &[(0, 0)]).unwrap();
+        let style = |game: &Game, x, y| cell_style(game.get(x, y), false);
+        assert_eq!(style(&game, 1, 1), Style::default().fg(Color::White));
+        game.get_mut(2, 2).state = CellState::Flagged;
+        assert_eq!(style(&game, 2, 2), Style::default().fg(Color::Red));
+        // The cursor keeps the color, lightened, on its background.
+        let cursor = cell_style(game.get(2, 2), true);
+        assert_eq!((cursor.fg, cursor.bg), (Some(Color::LightRed), Some(Color::DarkGray)));
+        assert!(cursor.add_modifier.contains(Modifier::BOLD));
+        for count in 1..=8 {
+            let mut cell = *game.get(1, 1);
+            cell.state = CellState::Revealed;
+            cell.neighbor_mines = count;
+            let expected = number_color(&theme::THEMES[0], count);
+            assert_eq!(cell_style(&cell, false), Style::default().fg(expected), "{}", count);
+        }
+        game.reveal(1, 0);
+        game.reveal(0, 0);
+        assert_eq!(style(&game, 0, 0), Style::default().fg(Color::Red));
+        let (glyph, hit) = post_mortem(&game, 0, 0, game.get(0, 0)).unwrap();
+        assert_eq!(glyph, '*');
+        assert_eq!(hit, Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD));
+    }
+
+    #[test]
+    fn a_large_board_scrolls_to_keep_the_cursor_in_a_small_terminal() {
+        theme::choose(theme::PaletteChoice::Basic);
+        let options = Options::default();
+        let look = BoardLook {
+            options: &options,
+            cursor: true,
+            heat: false,
+            planning: false,
+            odds: false,
+            dim_satisfied: false,
+            explosion: None,
+            flash: None,
+        };
+        let mut game = Game::new(30, 16, 99).unwrap();
+        let layout = BoardLayout::new(&game, &options, false);
+        for (columns, lines) in [(24, 10), (12, 6), (5, 4), (2, 2)] {
+            let mut terminal = Terminal::new(TestBackend::new(columns, lines)).unwrap();
+            let mut viewport = Viewport::default();
+            // Across the board and back, the way the arrows would go.
+            let path = (0..30).map(|x| (x, x * 15 / 29)).chain((0..30).rev().map(|x| (x, 15 - x * 15 / 29)));
+            for (x, y) in path {
+                (game.cursor_x, game.cursor_y) = (x, y);
+                terminal
+                    .draw(|f| draw_board(f, f.size(), Span::raw("扫雷"), &game, &look, &mut viewport))
+                    .unwrap();
+                // Inside the border and the frame around the cells.
+                let frame = (columns > 4 && lines > 4) as u16;
+                let visible = (columns.saturating_sub(2 + 2 * frame), lines.saturating_sub(2 + 2 * frame));
+                if visible.0 < layout.cell_width || visible.1 == 0 {
+                    continue;
+                }
+                let (column, row) = layout.cell_position(x, y, &options);
+                assert!(viewport.x <= column && column + layout.cell_width <= viewport.x + visible.0, "{}x{} at ({}, {})", columns, lines, x, y);
+                assert!(viewport.y <= row && row < viewport.y + visible.1, "{}x{} at ({}, {})", columns, lines, x, y);
+                let buffer = terminal.backend().buffer();
+                let on_screen = (1 + frame + column - viewport.x, 1 + frame + row - viewport.y);
+                assert_eq!(buffer.get(on_screen.0, on_screen.1).bg, Color::DarkGray, "{}x{} at ({}, {})", columns, lines, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn numbers_use_the_classic_colors() {
+        let colors: Vec<Color> = (1..=8).map(|count| number_color(&theme::THEMES[0], count)).collect();
+        assert_eq!(
+            colors,
+            [
+                Color::LightBlue,
+                Color::Green,
+                Color::LightRed,
+                Color::Blue,
+                Color::Red,
+                Color::Cyan,
+                Color::White,
+                Color::Gray,
+            ]
+        );
+    }
+}
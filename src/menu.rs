@@ -0,0 +1,196 @@
+use std::{borrow::Cow, io};
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use tui::{
+    backend::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Terminal,
+};
+
+use crate::{
+    config::{Difficulty, Settings},
+    custom_board, settings,
+};
+
+/// What the difficulty menu was answered with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MenuChoice {
+    /// An index into the menu's difficulties.
+    Difficulty(usize),
+    /// Width, height and mines, already checked by `custom_board`.
+    Custom((usize, usize, usize)),
+}
+
+/// The three fields of a custom board being typed in.
+#[derive(Default)]
+struct CustomForm {
+    fields: [String; 3],
+    focus: usize,
+    error: Option<String>,
+}
+
+impl CustomForm {
+    /// The board the fields describe, or why they don't make one.
+    fn board(&self) -> Result<(usize, usize, usize), String> {
+        let mut numbers = [0; 3];
+        for (number, field) in numbers.iter_mut().zip(&self.fields) {
+            *number = field.parse().map_err(|_| "请输入一个非负整数".to_string())?;
+        }
+        custom_board(numbers[0], numbers[1], numbers[2])
+    }
+}
+
+/// The difficulty menu, on its own alternate screen like the settings:
+/// the presets and a custom board as a list, moved through with the arrows
+/// and picked with Enter or the row's number (for the first nine). `s` opens the settings, and
+/// `q`, Esc or `Ctrl+D` give `None`. `message`, if any, is shown under the
+/// list, for a choice that couldn't be started.
+pub fn select_difficulty(
+    difficulties: &[Difficulty],
+    settings: &mut Settings,
+    message: Option<&str>,
+) -> io::Result<Option<MenuChoice>> {
+    let mut selected = 0;
+    loop {
+        match run(difficulties, &mut selected, message)? {
+            Answer::Settings => settings::edit(settings)?,
+            Answer::Done(choice) => return Ok(choice),
+        }
+    }
+}
+
+enum Answer {
+    Settings,
+    Done(Option<MenuChoice>),
+}
+
+/// One stay on the menu's screen, until a choice or a visit to the
+/// settings, which take over the alternate screen.
+fn run(difficulties: &[Difficulty], selected: &mut usize, message: Option<&str>) -> io::Result<Answer> {
+    // The presets, then a custom board.
+    let rows = difficulties.len() + 1;
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    terminal.clear()?;
+
+    let mut form: Option<CustomForm> = None;
+    let result = loop {
+        let drawn = terminal.draw(|f| {
+            let lines = match &form {
+                Some(form) => form_lines(form),
+                None => menu_lines(difficulties, *selected, message),
+            };
+            let block = Block::default().borders(Borders::ALL).title(Span::styled("扫雷", Style::default().fg(Color::Yellow)));
+            f.render_widget(Paragraph::new(lines).block(block), f.size());
+        });
+        if let Err(err) = drawn {
+            break Err(err);
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(err) => break Err(err),
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && matches!(key.code, KeyCode::Char('c' | 'd')) {
+            break Ok(Answer::Done(None));
+        }
+        if let Some(custom) = form.as_mut() {
+            match key.code {
+                KeyCode::Char(c) if c.is_ascii_digit() => custom.fields[custom.focus].push(c),
+                KeyCode::Backspace => {
+                    custom.fields[custom.focus].pop();
+                }
+                KeyCode::Up | KeyCode::BackTab => custom.focus = (custom.focus + 2) % 3,
+                KeyCode::Down | KeyCode::Tab => custom.focus = (custom.focus + 1) % 3,
+                KeyCode::Enter if custom.focus < 2 => custom.focus += 1,
+                KeyCode::Enter => match custom.board() {
+                    Ok(board) => break Ok(Answer::Done(Some(MenuChoice::Custom(board)))),
+                    Err(err) => custom.error = Some(err),
+                },
+                KeyCode::Esc => form = None,
+                _ => {}
+            }
+            continue;
+        }
+        let picked = match key.code {
+            KeyCode::Up => {
+                *selected = (*selected + rows - 1) % rows;
+                None
+            }
+            KeyCode::Down => {
+                *selected = (*selected + 1) % rows;
+                None
+            }
+            KeyCode::Enter => Some(*selected),
+            KeyCode::Char(c @ '1'..='9') => Some(c as usize - '1' as usize).filter(|&row| row < rows),
+            KeyCode::Char('s') => break Ok(Answer::Settings),
+            KeyCode::Char('q') | KeyCode::Esc => break Ok(Answer::Done(None)),
+            _ => None,
+        };
+        match picked {
+            Some(row) if row == difficulties.len() => {
+                *selected = row;
+                form = Some(CustomForm::default());
+            }
+            Some(choice) => break Ok(Answer::Done(Some(MenuChoice::Difficulty(choice)))),
+            None => {}
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show)?;
+    result
+}
+
+fn highlight(on: bool) -> Style {
+    if on {
+        Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    }
+}
+
+fn hint<'a>(text: impl Into<Cow<'a, str>>) -> Spans<'a> {
+    Spans::from(Span::styled(text, Style::default().fg(Color::DarkGray)))
+}
+
+fn menu_lines<'a>(difficulties: &[Difficulty], selected: usize, message: Option<&'a str>) -> Vec<Spans<'a>> {
+    let mut lines = vec![Spans::from(" 选择难度:"), Spans::from("")];
+    let presets = difficulties.iter().map(|Difficulty { name, width, height, mines }| {
+        format!("{} ({}x{}, {} 雷)", name, width, height, mines)
+    });
+    for (row, name) in presets.chain(["自定义".to_string()]).enumerate() {
+        lines.push(Spans::from(Span::styled(format!(" {}. {} ", row + 1, name), highlight(row == selected))));
+    }
+    lines.push(Spans::from(""));
+    if let Some(message) = message {
+        lines.push(Spans::from(Span::styled(format!(" {}", message), Style::default().fg(Color::Red))));
+    }
+    let numbered = (difficulties.len() + 1).min(9);
+    lines.push(hint(format!(" ↑/↓ 选择  Enter 或 1-{} 开始  s 设置  q 退出", numbered)));
+    lines
+}
+
+fn form_lines(form: &CustomForm) -> Vec<Spans<'_>> {
+    let mut lines = vec![Spans::from(" 自定义棋盘:"), Spans::from("")];
+    for (row, (name, value)) in ["宽度", "高度", "地雷数"].iter().zip(&form.fields).enumerate() {
+        let cursor = if row == form.focus { "_" } else { "" };
+        lines.push(Spans::from(Span::styled(format!(" {}: {}{} ", name, value, cursor), highlight(row == form.focus))));
+    }
+    lines.push(Spans::from(""));
+    if let Some(error) = &form.error {
+        lines.push(Spans::from(Span::styled(format!(" {}", error), Style::default().fg(Color::Red))));
+    }
+    lines.push(hint(" 输入数字  ↑/↓ 切换  Enter 下一项/开始  Esc 返回"));
+    lines
+}
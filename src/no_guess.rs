@@ -0,0 +1,141 @@
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{solver::Deduction, CellState, Game, GameConfig, MineTrigger};
+
+/// Boards tried before settling for one that needs a guess. Dense boards are
+/// rarely solvable by logic alone, so this is what bounds the wait on expert.
+pub const NO_GUESS_ATTEMPTS: usize = 500;
+
+/// How a `--no-guess` board came about, for the status bar.
+#[derive(Clone, Copy)]
+pub struct GenerationReport {
+    /// Candidate boards thrown away because they needed a guess.
+    pub rejected: usize,
+    pub elapsed: Duration,
+    /// False if the attempt cap was hit and the last board was kept anyway.
+    pub solvable: bool,
+    pub finished_at: Instant,
+}
+
+impl Game {
+    /// A `width` by `height` board with `mines` mines that can be solved
+    /// without guessing from a first reveal at `first_click`, with that
+    /// reveal already made. `generation` says how it went, including whether
+    /// `NO_GUESS_ATTEMPTS` ran out first.
+    pub fn new_no_guess(width: usize, height: usize, mines: usize, first_click: (usize, usize)) -> Result<Self, String> {
+        let (x, y) = first_click;
+        if x >= width || y >= height {
+            return Err(format!("起始格 ({},{}) 不在 {}x{} 的棋盘上", x + 1, y + 1, width, height));
+        }
+        let mut game = Self::from_config(GameConfig::default().size(width, height).mines(mines).no_guess(true))?;
+        game.reveal(x, y);
+        game.process_pending(usize::MAX);
+        Ok(game)
+    }
+
+    /// Regenerates the board until it can be solved from a first reveal at
+    /// `(x, y)` without guessing, up to `NO_GUESS_ATTEMPTS` boards. Each new
+    /// seed is drawn from the previous one, so a seeded game still always
+    /// ends up on the same board.
+    pub fn make_solvable(&mut self, x: usize, y: usize) {
+        let started = Instant::now();
+        let mut rejected = 0;
+        let mut solvable = self.solvable_from(x, y);
+        while !solvable && rejected + 1 < NO_GUESS_ATTEMPTS {
+            rejected += 1;
+            self.seed = StdRng::seed_from_u64(self.seed).random();
+            for cell in self.cells.iter_mut() {
+                cell.is_mine = false;
+            }
+            self.place_mines();
+            self.calculate_neighbors();
+            if self.config.big_opening {
+                self.clear_opening(x, y);
+            }
+            solvable = self.solvable_from(x, y);
+        }
+        self.generation = Some(GenerationReport {
+            rejected,
+            elapsed: started.elapsed(),
+            solvable,
+            finished_at: Instant::now(),
+        });
+    }
+
+    /// Plays a copy of the board from a reveal at `(x, y)`, making only the
+    /// moves `deduce` can prove, and reports whether that wins. Proven mines
+    /// are opened rather than flagged, since the solver only trusts a mine
+    /// it can see.
+    fn solvable_from(&self, x: usize, y: usize) -> bool {
+        let mut sim = self.clone();
+        // Flags placed before the first reveal are guesses, not knowledge.
+        for cell in sim.cells.iter_mut() {
+            if cell.state == CellState::Flagged {
+                cell.state = CellState::Hidden;
+            }
+        }
+        sim.flags = 0;
+        // Judged under the plain rules: one-cell reveals, and any mine loses.
+        sim.config = sim.config.clone().strict_flag_limit(false).gentle(false).mine_trigger(MineTrigger::Lose);
+        // Keeps `begin` from running again inside the copy.
+        sim.start_time = Some(Instant::now());
+        sim.reveal(x, y);
+        loop {
+            sim.process_pending(usize::MAX);
+            if sim.game_over {
+                return false;
+            }
+            if sim.victory {
+                return true;
+            }
+            // The full solver is slow on big boards, so it only runs once
+            // the single-number rules are exhausted.
+            let mut deductions = sim.obvious_deductions();
+            if deductions.is_empty() {
+                deductions = sim.deduce();
+            }
+            if deductions.is_empty() {
+                return false;
+            }
+            for deduction in deductions {
+                match deduction {
+                    Deduction::Safe(cx, cy) => sim.reveal(cx, cy),
+                    Deduction::Mine(cx, cy) => sim.get_mut(cx, cy).state = CellState::Revealed,
+                }
+            }
+        }
+    }
+
+    /// What single numbers prove on their own: a number with all its mines
+    /// stepped on makes its other hidden neighbors safe, and one with exactly
+    /// as many unopened neighbors as missing mines makes them all mines.
+    /// Flags prove nothing, and flagged cells are never reported.
+    pub fn obvious_deductions(&self) -> Vec<Deduction> {
+        let mut found = Vec::new();
+        for (x, y, cell) in self.iter_cells() {
+            if cell.state != CellState::Revealed || cell.is_mine || cell.neighbor_mines == 0 {
+                continue;
+            }
+            let marked = self.neighbors(x, y).filter(|&(nx, ny)| self.get(nx, ny).known_mine()).count();
+            let hidden: Vec<_> = self
+                .neighbors(x, y)
+                .filter(|&(nx, ny)| self.get(nx, ny).unseen())
+                .collect();
+            if hidden.is_empty() || marked > cell.neighbor_mines as usize {
+                continue;
+            }
+            let missing = cell.neighbor_mines as usize - marked;
+            let covered = hidden.iter().copied().filter(|&(nx, ny)| self.get(nx, ny).covered());
+            if missing == 0 {
+                found.extend(covered.map(|(nx, ny)| Deduction::Safe(nx, ny)));
+            } else if missing == hidden.len() {
+                found.extend(covered.map(|(nx, ny)| Deduction::Mine(nx, ny)));
+            }
+        }
+        found.sort();
+        found.dedup();
+        found
+    }
+}
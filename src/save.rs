@@ -0,0 +1,387 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{scores::config_dir, AutoFirst, Cell, CellState, Game, GameConfig, MineTrigger, Topology};
+
+/// Minimum time between two autosaves.
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An unfinished game as written to `autosave.json`. Rows hold one letter
+/// per cell: `.`/`*` hidden safe/mine, `f`/`F` flagged safe/mine, `?`/`!`
+/// questioned safe/mine and `o`/`x` revealed safe/mine. The rules (`--strict-flags`, `--gentle`, ...)
+/// are not stored and come from the command line of the resuming run.
+/// Neither are the counters (flags, revealed cells, mines): they are
+/// recounted from the rows on load, so they can't disagree with the board.
+/// A count added to the file by hand is an unknown field and ignored.
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    pub difficulty: usize,
+    width: usize,
+    height: usize,
+    mines: usize,
+    seed: u64,
+    rows: Vec<String>,
+    cursor: (usize, usize),
+    /// `None` before the first reveal.
+    elapsed_ms: Option<u64>,
+    lives: u32,
+    exploded: Vec<(usize, usize)>,
+    /// Decides every neighbor count, so unlike the other rules it is kept.
+    #[serde(default)]
+    hex: bool,
+}
+
+impl SavedGame {
+    pub fn of(game: &Game, difficulty: usize) -> Self {
+        let rows = game
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match (cell.state, cell.is_mine) {
+                        (CellState::Hidden, false) => '.',
+                        (CellState::Hidden, true) => '*',
+                        (CellState::Flagged, false) => 'f',
+                        (CellState::Flagged, true) => 'F',
+                        (CellState::Questioned, false) => '?',
+                        (CellState::Questioned, true) => '!',
+                        (CellState::Revealed, false) => 'o',
+                        (CellState::Revealed, true) => 'x',
+                    })
+                    .collect()
+            })
+            .collect();
+        SavedGame {
+            difficulty,
+            width: game.width,
+            height: game.height,
+            mines: game.mines,
+            seed: game.seed,
+            rows,
+            cursor: (game.cursor_x, game.cursor_y),
+            elapsed_ms: game.start_time.map(|_| game.elapsed().as_millis() as u64),
+            lives: game.lives,
+            exploded: game.exploded.clone(),
+            hex: game.config.topology == Topology::Hex,
+        }
+    }
+
+    /// Rebuilds the saved game under the rules in `config`, with the clock
+    /// running from where it stopped.
+    pub fn restore(self, config: GameConfig) -> Result<Game, String> {
+        let invalid = || "存档已损坏".to_string();
+        if self.width == 0 || self.height == 0 {
+            return Err(invalid());
+        }
+        if self.rows.len() != self.height || self.rows.iter().any(|r| r.chars().count() != self.width) {
+            return Err(invalid());
+        }
+        let topology = if self.hex { Topology::Hex } else { Topology::Square };
+        let config = config.size(self.width, self.height).mines(self.mines).seed(self.seed).topology(topology);
+        // The saved cells replace whatever an automatic opening would reveal.
+        let mut game = Game::from_config(config.clone().auto_first(AutoFirst::Off).first_cell(None))?;
+        for (y, row) in self.rows.iter().enumerate() {
+            for (x, letter) in row.chars().enumerate() {
+                let (state, is_mine) = match letter {
+                    '.' => (CellState::Hidden, false),
+                    '*' => (CellState::Hidden, true),
+                    'f' => (CellState::Flagged, false),
+                    'F' => (CellState::Flagged, true),
+                    '?' => (CellState::Questioned, false),
+                    '!' => (CellState::Questioned, true),
+                    'o' => (CellState::Revealed, false),
+                    'x' => (CellState::Revealed, true),
+                    _ => return Err(invalid()),
+                };
+                *game.get_mut(x, y) = Cell { is_mine, state, neighbor_mines: 0 };
+            }
+        }
+        // The layout may have moved since generation (big opening, no-guess),
+        // so the saved cells are the truth, not the seed.
+        game.calculate_neighbors();
+        let cells = game.cells.iter();
+        game.mines = cells.clone().filter(|c| c.is_mine).count();
+        game.flags = cells.clone().filter(|c| c.state == CellState::Flagged).count();
+        game.revealed_safe = cells.filter(|c| c.state == CellState::Revealed && !c.is_mine).count();
+        game.cursor_x = self.cursor.0.min(self.width - 1);
+        game.cursor_y = self.cursor.1.min(self.height - 1);
+        game.start_time = self
+            .elapsed_ms
+            .and_then(|ms| Instant::now().checked_sub(Duration::from_millis(ms)));
+        game.lives = self.lives;
+        // Every mine hit is one revealed mine, listed once; anything else
+        // would count a mistake twice or one that isn't on the board.
+        let mut hit = self.exploded.clone();
+        hit.sort();
+        hit.dedup();
+        let on_board = |&(x, y): &(usize, usize)| {
+            x < game.width && y < game.height && {
+                let cell = game.get(x, y);
+                cell.is_mine && cell.state == CellState::Revealed
+            }
+        };
+        if hit.len() != self.exploded.len() || !hit.iter().all(on_board) {
+            return Err(invalid());
+        }
+        game.exploded = self.exploded;
+        // The moves that led here weren't saved.
+        game.history = None;
+        game.config = config;
+        Ok(game)
+    }
+
+    pub fn load() -> Option<Self> {
+        let text = fs::read_to_string(autosave_path()?).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Reads a game written with the save key (or a copy of the autosave)
+    /// for `--resume <file>`.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("无法读取 {}: {}", path.display(), err))?;
+        serde_json::from_str(&text).map_err(|err| format!("无法解析 {}: {}", path.display(), err))
+    }
+
+    /// Writes the game to `saved.json` in the config directory, where the
+    /// autosave can't replace or remove it, and returns the path.
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = config_dir().ok_or("找不到配置目录")?.join("saved.json");
+        let json = serde_json::to_string(self).map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        fs::write(&path, json).map_err(|err| format!("无法写入 {}: {}", path.display(), err))?;
+        Ok(path)
+    }
+}
+
+/// The game as `--dump-state` writes it, for `jq` and other tools: the
+/// saved form, plus what can be worked out from it and the rules it is
+/// played under.
+#[derive(Serialize)]
+pub struct StateDump {
+    #[serde(flatten)]
+    saved: SavedGame,
+    /// `playing`, `won` or `lost`.
+    state: &'static str,
+    flags: usize,
+    /// Mines minus flags and mines hit; negative with too many flags.
+    remaining_mines: i64,
+    revealed_safe: usize,
+    safe_cells: usize,
+    rules: Rules,
+}
+
+#[derive(Serialize)]
+struct Rules {
+    /// `lose`, `lives`, `practice` or `zen`.
+    mine_trigger: &'static str,
+    timer: bool,
+    strict_flags: bool,
+    gentle: bool,
+    big_opening: bool,
+    no_guess: bool,
+}
+
+impl StateDump {
+    pub fn of(game: &Game, difficulty: usize) -> Self {
+        let state = match (game.game_over, game.victory) {
+            (true, _) => "lost",
+            (_, true) => "won",
+            _ => "playing",
+        };
+        let config = &game.config;
+        StateDump {
+            saved: SavedGame::of(game, difficulty),
+            state,
+            flags: game.flags,
+            remaining_mines: game.remaining_mines(),
+            revealed_safe: game.revealed_safe,
+            safe_cells: game.safe_cells(),
+            rules: Rules {
+                mine_trigger: match config.mine_trigger {
+                    MineTrigger::Lose => "lose",
+                    MineTrigger::Forgiving { .. } => "lives",
+                    MineTrigger::Practice => "practice",
+                    MineTrigger::Zen => "zen",
+                },
+                timer: config.timer,
+                strict_flags: config.strict_flag_limit,
+                gentle: config.gentle,
+                big_opening: config.big_opening,
+                no_guess: config.no_guess,
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Writes `autosave.json` at most once per `AUTOSAVE_INTERVAL`, and only
+/// after something changed. The file is written on a background thread so a
+/// slow disk never stalls the event loop.
+#[derive(Default)]
+pub struct Autosaver {
+    dirty: bool,
+    last_save: Option<Instant>,
+    writing: Option<JoinHandle<()>>,
+}
+
+impl Autosaver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notes that the game changed since the last save.
+    pub fn touch(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Saves `game` in the background if it changed and the interval has
+    /// passed. Finished games remove the file instead.
+    pub fn tick(&mut self, game: &Game, difficulty: usize) {
+        if game.game_over || game.victory {
+            if self.dirty {
+                self.dirty = false;
+                self.discard();
+            }
+            return;
+        }
+        let due = self.last_save.is_none_or(|at| at.elapsed() >= AUTOSAVE_INTERVAL);
+        let idle = self.writing.as_ref().is_none_or(JoinHandle::is_finished);
+        if !due || !idle {
+            return;
+        }
+        if let Some((path, json)) = self.snapshot(game, difficulty) {
+            self.last_save = Some(Instant::now());
+            self.writing = Some(thread::spawn(move || write(path, json)));
+        }
+    }
+
+    /// Writes any change still held back by the interval, and waits for it.
+    /// Called on quit so the last moves aren't lost.
+    pub fn flush(&mut self, game: &Game, difficulty: usize) {
+        if let Some(writing) = self.writing.take() {
+            let _ = writing.join();
+        }
+        if game.game_over || game.victory {
+            return;
+        }
+        if let Some((path, json)) = self.snapshot(game, difficulty) {
+            write(path, json);
+        }
+    }
+
+    /// The file to write if there is something new worth saving. Games that
+    /// haven't started yet are not.
+    fn snapshot(&mut self, game: &Game, difficulty: usize) -> Option<(PathBuf, String)> {
+        if !self.dirty || game.start_time.is_none() {
+            return None;
+        }
+        let json = serde_json::to_string(&SavedGame::of(game, difficulty)).ok()?;
+        self.dirty = false;
+        Some((autosave_path()?, json))
+    }
+
+    /// Removes the autosave, after any write still in flight.
+    pub fn discard(&mut self) {
+        if let Some(writing) = self.writing.take() {
+            let _ = writing.join();
+        }
+        if let Some(path) = autosave_path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Failures are ignored; the next change tries again.
+fn write(path: PathBuf, json: String) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = fs::write(path, json);
+}
+
+fn autosave_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("autosave.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x2 save with one mine at (0,0), and `change` made to its JSON.
+    fn restored(change: impl FnOnce(&mut serde_json::Value)) -> Result<Game, String> {
+        let mut json = serde_json::json!({
+            "difficulty": 0,
+            "width": 3,
+            "height": 2,
+            "mines": 1,
+            "seed": 7,
+            "rows": ["*..", "..o"],
+            "cursor": [2, 1],
+            "elapsed_ms": 1500,
+            "lives": 0,
+            "exploded": [],
+        });
+        change(&mut json);
+        let saved: SavedGame = serde_json::from_value(json).map_err(|err| err.to_string())?;
+        saved.restore(GameConfig::default())
+    }
+
+    #[test]
+    fn an_empty_board_is_refused() {
+        let empty = restored(|json| {
+            json["width"] = 0.into();
+            json["height"] = 0.into();
+            json["rows"] = serde_json::json!([]);
+        });
+        assert_eq!(empty.err(), Some("存档已损坏".to_string()));
+    }
+
+    #[test]
+    fn flags_are_recounted_from_the_rows() {
+        let game = restored(|json| {
+            json["rows"] = serde_json::json!(["Ff.", "..o"]);
+            json["flags"] = 5.into();
+        })
+        .unwrap();
+        let flagged = game.cells.iter().filter(|cell| cell.state == CellState::Flagged).count();
+        assert_eq!(flagged, 2);
+        assert_eq!(game.flags, flagged);
+    }
+
+    #[test]
+    fn a_mine_hit_is_listed_once_and_on_the_board() {
+        let hit = |exploded: serde_json::Value| {
+            restored(|json| {
+                json["rows"] = serde_json::json!(["x..", "..o"]);
+                json["exploded"] = exploded;
+            })
+        };
+        assert_eq!(hit(serde_json::json!([[0, 0]])).unwrap().exploded, [(0, 0)]);
+        assert!(hit(serde_json::json!([[0, 0], [0, 0]])).is_err());
+        assert!(hit(serde_json::json!([[0, 0], [5, 0]])).is_err());
+    }
+
+    #[test]
+    fn the_state_dump_has_the_keys_tools_read() {
+        let game = restored(|_| {}).unwrap();
+        let dump: serde_json::Value = serde_json::from_str(&StateDump::of(&game, 0).to_json()).unwrap();
+        for key in ["state", "flags", "remaining_mines", "rows", "cursor", "rules"] {
+            assert!(dump.get(key).is_some(), "no {}", key);
+        }
+        assert_eq!(dump["state"], "playing");
+        assert_eq!(dump["remaining_mines"], 1);
+        assert_eq!(dump["rows"], serde_json::json!(["*..", "..o"]));
+        assert_eq!(dump["cursor"], serde_json::json!([2, 1]));
+        assert_eq!(dump["rules"]["mine_trigger"], "lose");
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use serde::{Deserialize, Serialize};
+
+/// A best time together with the day it was set.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    /// Seconds, to hundredths. Older files stored whole seconds, which still
+    /// load as the same value.
+    pub time: f64,
+    /// UTC date as `YYYY-MM-DD`; `None` for records saved before dates were kept.
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+/// Best time for each of the three difficulties.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub records: [Option<ScoreRecord>; 3],
+}
+
+impl Leaderboard {
+    /// Records `time` if it beats the current best, returning whether it did.
+    pub fn update(&mut self, difficulty: usize, time: f64, date: String) -> bool {
+        let is_best = self.records[difficulty].as_ref().is_none_or(|best| time < best.time);
+        if is_best {
+            self.records[difficulty] = Some(ScoreRecord { time, date: Some(date) });
+        }
+        is_best
+    }
+}
+
+/// Entries kept per difficulty on the leaderboard.
+pub const LEADERBOARD_SIZE: usize = 20;
+
+/// Characters a leaderboard name may have, fewest and most.
+pub const NAME_LENGTH: (usize, usize) = (3, 12);
+
+/// One win on the leaderboard.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    /// Seconds, to hundredths.
+    pub time: f64,
+    /// UTC date as `YYYY-MM-DD`.
+    pub date: String,
+}
+
+/// Games finished at one difficulty, and the runs of wins among them.
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Tally {
+    pub played: u32,
+    pub won: u32,
+    /// Wins since the last loss.
+    pub streak: u32,
+    pub best_streak: u32,
+}
+
+impl Tally {
+    pub fn record(&mut self, won: bool) {
+        self.played += 1;
+        if won {
+            self.won += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    /// Share of games won, from 0 to 1; `None` before the first game.
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.played > 0).then(|| self.won as f64 / self.played as f64)
+    }
+}
+
+/// Records kept between runs, stored as JSON in the user's config directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Scores {
+    /// The fastest named wins for each difficulty, fastest first, at most
+    /// `LEADERBOARD_SIZE` of them.
+    #[serde(default)]
+    pub leaderboard: [Vec<Entry>; 3],
+    #[serde(default)]
+    pub best: Leaderboard,
+    /// Games won and lost for each difficulty, under the same conditions as
+    /// best times. Games left unfinished aren't counted.
+    #[serde(default)]
+    pub tallies: [Tally; 3],
+    /// Completion time in seconds for each won daily challenge, keyed by date.
+    #[serde(default)]
+    pub daily: BTreeMap<String, f64>,
+}
+
+impl Scores {
+    /// Whether a win in `time` would make the leaderboard for `difficulty`.
+    pub fn qualifies(&self, difficulty: usize, time: f64) -> bool {
+        let entries = &self.leaderboard[difficulty];
+        entries.len() < LEADERBOARD_SIZE || entries.last().is_some_and(|last| time < last.time)
+    }
+
+    /// Puts `entry` in its place on the leaderboard, after any equal time,
+    /// dropping whatever falls off the end. Returns its rank from 0, or
+    /// `None` if it was too slow to stay.
+    pub fn add_entry(&mut self, difficulty: usize, entry: Entry) -> Option<usize> {
+        let entries = &mut self.leaderboard[difficulty];
+        let rank = entries.partition_point(|other| other.time <= entry.time);
+        entries.insert(rank, entry);
+        entries.truncate(LEADERBOARD_SIZE);
+        (rank < LEADERBOARD_SIZE).then_some(rank)
+    }
+
+    /// Loads the scores file. A missing or unreadable file yields empty scores;
+    /// one that can't be parsed is first renamed to `scores.json.bad`, so the
+    /// next save doesn't write over what might still be recovered by hand.
+    pub fn load() -> Self {
+        let Some(path) = scores_path() else {
+            return Self::default();
+        };
+        let Ok(text) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|_| {
+            let _ = fs::rename(&path, path.with_extension("json.bad"));
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = scores_path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("minesweeper"));
+    }
+    if let Some(dir) = env::var_os("APPDATA") {
+        return Some(PathBuf::from(dir).join("minesweeper"));
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("minesweeper"))
+}
+
+fn scores_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("scores.json"))
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn utc_date_today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since 1970-01-01 into a proleptic Gregorian (year, month, day).
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Seed for the daily challenge. Uses FNV-1a rather than `DefaultHasher` so
+/// every build and platform derives the same board from the same date.
+pub fn daily_seed(date: &str) -> u64 {
+    date.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,103 @@
+use std::io;
+
+use crossterm::{
+    cursor::{Hide, Show},
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use tui::{
+    backend::CrosstermBackend,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Terminal,
+};
+
+use crate::{config::Settings, theme};
+
+/// Rows of the settings screen, top to bottom.
+const ROWS: usize = 5;
+
+/// The settings screen, opened with `s` from the difficulty menu: one row
+/// per setting, moved through with the arrows and changed with space, enter
+/// or left/right. `menu::select_difficulty` opens it between two stays on
+/// the menu's alternate screen, so it takes its own alternate screen and
+/// hands back to the menu on Esc or `q`. Changes apply to the games started
+/// afterwards; the palette and theme apply at once.
+pub fn edit(settings: &mut Settings) -> io::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    terminal.clear()?;
+
+    let mut selected = 0;
+    let result = loop {
+        let drawn = terminal.draw(|f| {
+            let mut lines = vec![Spans::from("")];
+            for (row, (name, value)) in rows(settings).into_iter().enumerate() {
+                let style = if row == selected {
+                    Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                lines.push(Spans::from(Span::styled(format!(" {}: {} ", name, value), style)));
+            }
+            lines.push(Spans::from(""));
+            lines.push(Spans::from(Span::styled(
+                "↑/↓ 选择  空格 切换  Esc 返回 (对之后的新棋盘生效)",
+                Style::default().fg(Color::DarkGray),
+            )));
+            let block = Block::default().borders(Borders::ALL).title(Span::styled("设置", Style::default().fg(Color::Yellow)));
+            f.render_widget(Paragraph::new(lines).block(block), f.size());
+        });
+        if let Err(err) = drawn {
+            break Err(err);
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(err) => break Err(err),
+        };
+        match key.code {
+            KeyCode::Up => selected = (selected + ROWS - 1) % ROWS,
+            KeyCode::Down => selected = (selected + 1) % ROWS,
+            KeyCode::Char(' ') | KeyCode::Enter | KeyCode::Left | KeyCode::Right => change(settings, selected),
+            KeyCode::Esc | KeyCode::Char('q') => break Ok(()),
+            _ => {}
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, Show)?;
+    result
+}
+
+/// Each row's name and current value.
+fn rows(settings: &Settings) -> [(&'static str, &'static str); ROWS] {
+    let on_off = |on: bool| if on { "开" } else { "关" };
+    [
+        ("调色板", settings.palette.label()),
+        ("主题", theme::THEME_LABELS[settings.theme]),
+        ("提示音", on_off(settings.sound)),
+        ("新手模式 (翻开 3x3)", on_off(settings.gentle)),
+        ("严格旗帜上限", on_off(settings.strict_flags)),
+    ]
+}
+
+fn change(settings: &mut Settings, row: usize) {
+    match row {
+        0 => {
+            settings.palette = settings.palette.next();
+            theme::choose(settings.palette);
+        }
+        1 => {
+            settings.theme = (settings.theme + 1) % theme::THEMES.len();
+            theme::choose_theme(settings.theme);
+        }
+        2 => settings.sound = !settings.sound,
+        3 => settings.gentle = !settings.gentle,
+        _ => settings.strict_flags = !settings.strict_flags,
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{CellState, Game};
+
+/// A cell whose content follows from the visible numbers alone.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Deduction {
+    Safe(usize, usize),
+    Mine(usize, usize),
+}
+
+/// "Exactly `mines` of `cells` are mines."
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Constraint {
+    cells: BTreeSet<(usize, usize)>,
+    mines: usize,
+}
+
+/// The total mine count is only used once at most this many cells are
+/// unknown. Counting matters in the endgame, and earlier its constraint
+/// over every unknown cell makes subset subtraction blow up.
+const GLOBAL_MAX_CELLS: usize = 24;
+
+/// Rounds of subset subtraction before the rules are applied. Each round can
+/// only shrink constraints, so a handful covers every practical chain.
+const SUBSET_ROUNDS: usize = 4;
+
+/// Search steps spent on one region's mine range before giving up on it.
+/// Ordinary frontiers need a few hundred; a long open border can need far
+/// more, and a status line isn't worth stalling a frame for.
+const REGION_SEARCH_STEPS: usize = 100_000;
+
+/// Hidden cells joined through the numbers over them, with those numbers.
+type Region = (BTreeSet<(usize, usize)>, Vec<Constraint>);
+
+/// The mines a connected stretch of the frontier can still hold: hidden
+/// cells linked through the numbers they share, counted over every flagging
+/// those numbers allow. Nothing outside the region is taken into account.
+pub struct RegionBounds {
+    /// The region's first cell in reading order.
+    pub first: (usize, usize),
+    pub cells: usize,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Game {
+    /// Every hidden cell that is provably safe or provably a mine.
+    ///
+    /// Each revealed number is a constraint over its unknown neighbors, and
+    /// near the end the total mine count is one more over all unknown cells
+    /// (see `GLOBAL_MAX_CELLS`). Only mines already stepped on count as
+    /// mines: a flag may be wrong, so a flagged cell is as unknown as any
+    /// other, and it is worked out but never reported. Constraints are then
+    /// refined until nothing new follows:
+    ///
+    /// * a constraint needing 0 mines makes its cells safe, one needing as
+    ///   many mines as cells makes them all mines;
+    /// * when `A` is a subset of `B`, `B \ A` holds `B.mines - A.mines`;
+    /// * for overlapping `A` and `B`, if `B \ A` must hold all of the extra
+    ///   `B.mines - A.mines`, those cells are mines and `A \ B` is safe. This
+    ///   is what solves the 1-2-1 and 1-2-2-1 patterns.
+    pub fn deduce(&self) -> Vec<Deduction> {
+        let mut known: BTreeMap<(usize, usize), bool> = BTreeMap::new();
+        loop {
+            let constraints = self.refined_constraints(&known);
+            let found = apply_rules(&constraints);
+            let mut progressed = false;
+            for (cell, is_mine) in found {
+                progressed |= known.insert(cell, is_mine).is_none();
+            }
+            if !progressed {
+                break;
+            }
+        }
+        known
+            .into_iter()
+            .filter(|&((x, y), _)| self.get(x, y).covered())
+            .map(|((x, y), is_mine)| if is_mine { Deduction::Mine(x, y) } else { Deduction::Safe(x, y) })
+            .collect()
+    }
+
+    /// Constraints from the board with `known` cells substituted in, closed
+    /// under subset subtraction.
+    fn refined_constraints(&self, known: &BTreeMap<(usize, usize), bool>) -> BTreeSet<Constraint> {
+        let mut constraints = BTreeSet::new();
+        let mut unknown_total = BTreeSet::new();
+        let mut mines_left = self.mines as isize;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = self.get(x, y);
+                if cell.known_mine() {
+                    mines_left -= 1;
+                    continue;
+                }
+                match cell.state {
+                    CellState::Hidden | CellState::Questioned | CellState::Flagged => match known.get(&(x, y)) {
+                        Some(true) => mines_left -= 1,
+                        Some(false) => {}
+                        None => {
+                            unknown_total.insert((x, y));
+                        }
+                    },
+                    CellState::Revealed if cell.neighbor_mines > 0 => {
+                        let mut mines = cell.neighbor_mines as isize;
+                        let mut cells = BTreeSet::new();
+                        for (nx, ny) in self.neighbors(x, y) {
+                            let neighbor = self.get(nx, ny);
+                            match (neighbor.state, known.get(&(nx, ny))) {
+                                _ if neighbor.known_mine() => mines -= 1,
+                                _ if !neighbor.unseen() => {}
+                                (_, Some(true)) => mines -= 1,
+                                (_, None) => {
+                                    cells.insert((nx, ny));
+                                }
+                                _ => {}
+                            }
+                        }
+                        // A negative count means the known mines contradict
+                        // the number; nothing sound can be derived from it.
+                        if !cells.is_empty() && mines >= 0 {
+                            constraints.insert(Constraint { cells, mines: mines as usize });
+                        }
+                    }
+                    CellState::Revealed => {}
+                }
+            }
+        }
+        if !unknown_total.is_empty() && unknown_total.len() <= GLOBAL_MAX_CELLS && mines_left >= 0 {
+            constraints.insert(Constraint { cells: unknown_total, mines: mines_left as usize });
+        }
+
+        for _ in 0..SUBSET_ROUNDS {
+            let mut derived = Vec::new();
+            for a in &constraints {
+                for b in &constraints {
+                    if a.cells.len() < b.cells.len() && a.mines <= b.mines && a.cells.is_subset(&b.cells) {
+                        let cells: BTreeSet<_> = b.cells.difference(&a.cells).copied().collect();
+                        let candidate = Constraint { cells, mines: b.mines - a.mines };
+                        if !constraints.contains(&candidate) {
+                            derived.push(candidate);
+                        }
+                    }
+                }
+            }
+            if derived.is_empty() {
+                break;
+            }
+            constraints.extend(derived);
+        }
+        constraints
+    }
+
+    /// `RegionBounds` for every connected frontier region whose range could
+    /// be worked out within `REGION_SEARCH_STEPS`, in reading order.
+    pub fn region_mine_bounds(&self) -> Vec<RegionBounds> {
+        let mut bounds: Vec<_> = self
+            .frontier_regions()
+            .into_iter()
+            .filter_map(|(cells, members)| {
+                let search = search_region(&cells, &members)?;
+                let first = cells.iter().copied().min_by_key(|&(x, y)| (y, x))?;
+                Some(RegionBounds { first, cells: cells.len(), min: search.min, max: search.max })
+            })
+            .collect();
+        bounds.sort_by_key(|region| (region.first.1, region.first.0));
+        bounds
+    }
+
+    /// A covered cell that is provably safe, the one nearest the cursor.
+    /// Single numbers are tried first, since they are quick and are what a
+    /// player would spot; only then the full `deduce`.
+    pub fn find_safe_cell(&self) -> Option<(usize, usize)> {
+        let nearest = |deductions: Vec<Deduction>| {
+            deductions
+                .into_iter()
+                .filter_map(|deduction| match deduction {
+                    Deduction::Safe(x, y) => Some((x, y)),
+                    Deduction::Mine(..) => None,
+                })
+                .min_by_key(|&(x, y)| x.abs_diff(self.cursor_x) + y.abs_diff(self.cursor_y))
+        };
+        nearest(self.obvious_deductions()).or_else(|| nearest(self.deduce()))
+    }
+
+    /// The hidden cell to reveal when a guess can't be avoided: the one
+    /// least likely to be a mine, and among equals the one with the most
+    /// hidden neighbors, whose number says the most. `None` while some cell
+    /// is provably safe, since then no guess is needed.
+    pub fn safest_guess(&self) -> Option<(usize, usize)> {
+        if self.deduce().iter().any(|d| matches!(d, Deduction::Safe(..))) {
+            return None;
+        }
+        let hidden_around = |(x, y): (usize, usize)| {
+            self.neighbors(x, y).filter(|&(nx, ny)| self.get(nx, ny).covered()).count()
+        };
+        self.mine_chances()
+            .into_iter()
+            .filter(|&((x, y), _)| self.get(x, y).covered())
+            .min_by(|&(a, pa), &(b, pb)| pa.total_cmp(&pb).then(hidden_around(b).cmp(&hidden_around(a))))
+            .map(|(cell, _)| cell)
+    }
+
+    /// `mine_chances` by cell, for the probability overlay.
+    pub fn mine_probabilities(&self) -> HashMap<(usize, usize), f64> {
+        self.mine_chances().into_iter().collect()
+    }
+
+    /// The chance each hidden cell is a mine. A frontier cell's is the share
+    /// of its region's flaggings (see `search_region`) that put a mine on
+    /// it; every other hidden cell shares evenly in the mines left over.
+    /// Regions too large to search are left out, so their cells get none.
+    /// This weighs every flagging of a region alike, ignoring how the rest
+    /// of the board would have to fill in around it, so it is a guide rather
+    /// than an exact figure. In the order `safest_guess` breaks ties by.
+    fn mine_chances(&self) -> Vec<((usize, usize), f64)> {
+        let mut found = Vec::new();
+        let mut frontier = BTreeSet::new();
+        let mut expected = 0.0;
+        for (cells, members) in self.frontier_regions() {
+            frontier.extend(cells.iter().copied());
+            let Some(search) = search_region(&cells, &members) else { continue };
+            for (&cell, &count) in cells.iter().zip(&search.mine_counts) {
+                let chance = count as f64 / search.solutions as f64;
+                expected += chance;
+                found.push((cell, chance));
+            }
+        }
+        let marked = self.iter_cells().filter(|(_, _, cell)| cell.known_mine()).count();
+        let rest: Vec<_> = self
+            .iter_cells()
+            .filter(|&(x, y, cell)| cell.unseen() && !frontier.contains(&(x, y)))
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let left = (self.mines as f64 - marked as f64 - expected).max(0.0);
+        let chance = (left / rest.len() as f64).min(1.0);
+        found.extend(rest.into_iter().map(|cell| (cell, chance)));
+        found
+    }
+
+    /// The frontier split into regions: hidden cells next to numbers,
+    /// joined wherever one number touches cells of both.
+    fn frontier_regions(&self) -> Vec<Region> {
+        let mut regions: Vec<Region> = Vec::new();
+        for constraint in self.number_constraints() {
+            let (joined, apart): (Vec<_>, Vec<_>) =
+                regions.into_iter().partition(|(cells, _)| !cells.is_disjoint(&constraint.cells));
+            let mut cells = constraint.cells.clone();
+            let mut members = vec![constraint];
+            for (more_cells, more_members) in joined {
+                cells.extend(more_cells);
+                members.extend(more_members);
+            }
+            regions = apart;
+            regions.push((cells, members));
+        }
+        regions
+    }
+
+    /// One constraint per revealed number with unopened neighbors, over those
+    /// neighbors, less the mines already stepped on around it. Flagged
+    /// neighbors stay in, as for `deduce`.
+    fn number_constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        for (x, y, cell) in self.iter_cells() {
+            if cell.state != CellState::Revealed || cell.is_mine || cell.neighbor_mines == 0 {
+                continue;
+            }
+            let marked = self.neighbors(x, y).filter(|&(nx, ny)| self.get(nx, ny).known_mine()).count();
+            let cells: BTreeSet<_> = self
+                .neighbors(x, y)
+                .filter(|&(nx, ny)| self.get(nx, ny).unseen())
+                .collect();
+            // As in `refined_constraints`, a contradiction says nothing.
+            if !cells.is_empty() && marked <= cell.neighbor_mines as usize {
+                constraints.push(Constraint { cells, mines: cell.neighbor_mines as usize - marked });
+            }
+        }
+        constraints
+    }
+
+    /// Adjacent pairs that form a true 50/50: exactly one of the two is a
+    /// mine, and swapping them changes no number anywhere on the board, so
+    /// no amount of deduction can tell them apart. The cells next to one of
+    /// the pair but not the other must all be mines (numbers only show on
+    /// safe cells), which is the classic trap against a wall or mine cluster.
+    pub fn fifty_fifty_traps(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let mut traps = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // Looking only right and down visits each pair once.
+                for (bx, by) in [(x + 1, y), (x, y + 1)] {
+                    if bx >= self.width || by >= self.height {
+                        continue;
+                    }
+                    if self.get(x, y).is_mine == self.get(bx, by).is_mine {
+                        continue;
+                    }
+                    let around_a: Vec<_> = self.neighbors(x, y).collect();
+                    let around_b: Vec<_> = self.neighbors(bx, by).collect();
+                    let mut only_one = around_a
+                        .iter()
+                        .filter(|c| !around_b.contains(c))
+                        .chain(around_b.iter().filter(|c| !around_a.contains(c)))
+                        .filter(|&&c| c != (x, y) && c != (bx, by));
+                    let indistinguishable = only_one.all(|&(cx, cy)| self.get(cx, cy).is_mine);
+                    if indistinguishable {
+                        traps.push(((x, y), (bx, by)));
+                    }
+                }
+            }
+        }
+        traps
+    }
+}
+
+fn apply_rules(constraints: &BTreeSet<Constraint>) -> Vec<((usize, usize), bool)> {
+    let mut found = Vec::new();
+    for c in constraints {
+        if c.mines == 0 {
+            found.extend(c.cells.iter().map(|&cell| (cell, false)));
+        } else if c.mines == c.cells.len() {
+            found.extend(c.cells.iter().map(|&cell| (cell, true)));
+        }
+    }
+    if !found.is_empty() {
+        return found;
+    }
+    for a in constraints {
+        for b in constraints {
+            if b.mines <= a.mines || a.cells.is_disjoint(&b.cells) {
+                continue;
+            }
+            let only_b: Vec<_> = b.cells.difference(&a.cells).copied().collect();
+            if b.mines - a.mines == only_b.len() {
+                found.extend(only_b.into_iter().map(|cell| (cell, true)));
+                found.extend(a.cells.difference(&b.cells).map(|&cell| (cell, false)));
+            }
+        }
+    }
+    found
+}
+
+/// What `search_region` found over every flagging of a region's cells
+/// that meets all of its constraints.
+struct RegionSearch {
+    min: usize,
+    max: usize,
+    solutions: u64,
+    /// For each cell, in the region's order, how many of those flaggings
+    /// put a mine on it.
+    mine_counts: Vec<u64>,
+}
+
+/// Tries each of `cells` both ways, pruning as soon as a constraint can no
+/// longer be met. `None` if that takes over `REGION_SEARCH_STEPS`, or if the
+/// flags leave no way at all.
+fn search_region(cells: &BTreeSet<(usize, usize)>, constraints: &[Constraint]) -> Option<RegionSearch> {
+    let touching: Vec<Vec<usize>> = cells
+        .iter()
+        .map(|cell| (0..constraints.len()).filter(|&i| constraints[i].cells.contains(cell)).collect())
+        .collect();
+    let mut search = Search {
+        mines: vec![false; cells.len()],
+        touching,
+        needed: constraints.iter().map(|c| c.mines).collect(),
+        undecided: constraints.iter().map(|c| c.cells.len()).collect(),
+        found: RegionSearch { min: usize::MAX, max: 0, solutions: 0, mine_counts: vec![0; cells.len()] },
+        steps: 0,
+    };
+    if !search.run(0) || search.found.solutions == 0 {
+        return None;
+    }
+    Some(search.found)
+}
+
+/// The state of `search_region` partway through: the cells decided so far
+/// and, for each constraint, mines it still needs and cells not yet decided.
+struct Search {
+    mines: Vec<bool>,
+    touching: Vec<Vec<usize>>,
+    needed: Vec<usize>,
+    undecided: Vec<usize>,
+    found: RegionSearch,
+    steps: usize,
+}
+
+impl Search {
+    /// Decides cell `index` onwards. Returns false once the step budget
+    /// runs out.
+    fn run(&mut self, index: usize) -> bool {
+        self.steps += 1;
+        if self.steps > REGION_SEARCH_STEPS {
+            return false;
+        }
+        if index == self.touching.len() {
+            let mines = self.mines.iter().filter(|&&m| m).count();
+            self.found.min = self.found.min.min(mines);
+            self.found.max = self.found.max.max(mines);
+            self.found.solutions += 1;
+            for (count, &is_mine) in self.found.mine_counts.iter_mut().zip(&self.mines) {
+                *count += is_mine as u64;
+            }
+            return true;
+        }
+        for is_mine in [false, true] {
+            let fits = self.touching[index].iter().all(|&c| {
+                let needed = self.needed[c];
+                // Still reachable with one cell fewer left to fill.
+                if is_mine { needed > 0 } else { needed < self.undecided[c] }
+            });
+            if !fits {
+                continue;
+            }
+            for &c in &self.touching[index] {
+                self.needed[c] -= is_mine as usize;
+                self.undecided[c] -= 1;
+            }
+            self.mines[index] = is_mine;
+            let finished = self.run(index + 1);
+            for &c in &self.touching[index] {
+                self.needed[c] += is_mine as usize;
+                self.undecided[c] += 1;
+            }
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+}
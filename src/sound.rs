@@ -0,0 +1,79 @@
+use std::{
+    io::{self, Write},
+    sync::mpsc::{self, SyncSender},
+    thread,
+    time::Duration,
+};
+
+/// One beep.
+#[derive(Clone, Copy)]
+pub struct Tone {
+    pub hz: u32,
+    pub length: Duration,
+}
+
+impl Tone {
+    /// For a revealed safe cell with `count` mines around it. Empty cells get
+    /// a low tone of their own, and numbers climb a whole tone per mine.
+    pub fn for_count(count: u8) -> Self {
+        let hz = match count {
+            0 => 262,
+            n => (440.0 * 2f64.powf(f64::from(n - 1) / 6.0)) as u32,
+        };
+        Tone { hz, length: Duration::from_millis(40) }
+    }
+
+    /// For stepping on a mine: lower and longer than any number.
+    pub fn mine() -> Self {
+        Tone { hz: 110, length: Duration::from_millis(250) }
+    }
+}
+
+/// Plays tones through the terminal bell on a background thread, so the
+/// event loop never waits for one to finish. The Linux console honors the
+/// pitch and length; other terminals ignore those escapes and ring their
+/// usual bell.
+pub struct Sound {
+    queue: SyncSender<Tone>,
+}
+
+impl Sound {
+    pub fn new() -> Self {
+        let (queue, tones) = mpsc::sync_channel::<Tone>(1);
+        thread::spawn(move || {
+            for tone in tones {
+                ring(tone);
+            }
+        });
+        Sound { queue }
+    }
+
+    /// Queues `tone`. It is dropped if the last one is still playing, so a
+    /// burst of reveals never builds up a backlog.
+    pub fn play(&self, tone: Tone) {
+        let _ = self.queue.try_send(tone);
+    }
+}
+
+impl Drop for Sound {
+    /// Puts the console bell back to its default pitch and length.
+    fn drop(&mut self) {
+        let _ = write!(io::stdout(), "\x1b[10]\x1b[11]");
+    }
+}
+
+fn ring(tone: Tone) {
+    // One write, so it can't land inside an escape sequence being drawn.
+    let _ = io::stdout().write_all(
+        format!("\x1b[10;{}]\x1b[11;{}]\x07", tone.hz, tone.length.as_millis()).as_bytes(),
+    );
+    let _ = io::stdout().flush();
+    thread::sleep(tone.length);
+}
+
+/// The terminal's plain bell, for `--bell`. Rung from the event loop
+/// between frames, so it can't land inside an escape sequence being drawn.
+pub fn bell() {
+    let _ = io::stdout().write_all(b"\x07");
+    let _ = io::stdout().flush();
+}
@@ -0,0 +1,55 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::Game;
+
+impl Game {
+    /// Cells in the largest opening: a connected region of empty cells (no
+    /// mine, no neighboring mine) together with the numbers bordering it,
+    /// which is what a single click on it reveals. Zero on a board without
+    /// empty cells. Computed from the whole layout, hidden or not.
+    pub fn largest_opening(&self) -> usize {
+        let empty = |x: usize, y: usize| {
+            let cell = self.get(x, y);
+            !cell.is_mine && cell.neighbor_mines == 0
+        };
+        let mut visited = vec![false; self.width * self.height];
+        let mut largest = 0;
+        for (x, y, _) in self.iter_cells() {
+            if visited[y * self.width + x] || !empty(x, y) {
+                continue;
+            }
+            visited[y * self.width + x] = true;
+            let mut zeros = 0;
+            let mut border = BTreeSet::new();
+            let mut queue = VecDeque::from([(x, y)]);
+            while let Some((cx, cy)) = queue.pop_front() {
+                zeros += 1;
+                for (nx, ny) in self.neighbors(cx, cy) {
+                    if !empty(nx, ny) {
+                        border.insert((nx, ny));
+                    } else if !visited[ny * self.width + nx] {
+                        visited[ny * self.width + nx] = true;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            largest = largest.max(zeros + border.len());
+        }
+        largest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_largest_opening_counts_its_blanks_and_the_numbers_around_them() {
+        // A wall of mines down column 2 parts a narrow opening from a wide one:
+        // 3 blanks and 3 numbers on the left, 9 blanks and 3 numbers on the right.
+        let walled = Game::with_mines(7, 3, &[(2, 0), (2, 1), (2, 2)]).unwrap();
+        assert_eq!(walled.largest_opening(), 12);
+        // A mine in the middle leaves nothing but numbers.
+        assert_eq!(Game::with_mines(3, 3, &[(1, 1)]).unwrap().largest_opening(), 0);
+    }
+}
@@ -0,0 +1,152 @@
+use rand::{rngs::StdRng, seq::IndexedRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{event_log, solver::Deduction, CellState, Game};
+
+/// A single player action at board coordinates `(x, y)`.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Move {
+    Reveal(usize, usize),
+    Flag(usize, usize),
+    Chord(usize, usize),
+    /// A reveal under `--safe-reveals`, which may move a mine first.
+    SafeReveal(usize, usize),
+    /// Takes back the flag move just made at `(x, y)`.
+    UndoFlag(usize, usize),
+}
+
+impl Move {
+    /// Makes the move as the player: it is recorded in the game's history,
+    /// written to the `--log` file and counted in `Game::clicks` if it
+    /// changed anything.
+    pub fn apply(self, game: &mut Game) {
+        let (name, x, y) = match self {
+            Move::Reveal(x, y) => ("reveal", x, y),
+            Move::Flag(x, y) => ("flag", x, y),
+            Move::Chord(x, y) => ("chord", x, y),
+            Move::SafeReveal(x, y) => ("safe reveal", x, y),
+            Move::UndoFlag(x, y) => ("undo flag", x, y),
+        };
+        event_log::event(format_args!("{} ({},{})", name, x + 1, y + 1));
+        game.record(self);
+        let progress = |game: &Game| (game.revealed_safe, game.flags, game.exploded.len(), game.get(x, y).state);
+        let before = progress(game);
+        self.execute(game);
+        if progress(game) != before {
+            game.clicks += 1;
+        }
+    }
+
+    /// Just the effect on the board, for replaying a history.
+    pub fn execute(self, game: &mut Game) {
+        // Only a flag move can be undone, and only right after it.
+        let last_flag = match self {
+            Move::Flag(x, y) => Some((x, y, game.get(x, y).state)),
+            _ => None,
+        };
+        match self {
+            Move::Reveal(x, y) => game.reveal(x, y),
+            Move::Flag(x, y) => game.toggle_flag(x, y),
+            Move::Chord(x, y) => game.chord(x, y),
+            Move::SafeReveal(x, y) => game.safe_reveal(x, y),
+            Move::UndoFlag(x, y) => game.undo_flag(x, y),
+        }
+        game.last_flag = last_flag.filter(|&(x, y, before)| game.get(x, y).state != before);
+    }
+}
+
+/// Something that can play the game. Strategies should only look at what a
+/// player could see: cell states and the numbers on revealed cells.
+pub trait Strategy {
+    /// The next move to make, or `None` when the strategy has nothing to do.
+    fn next_move(&mut self, game: &Game) -> Option<Move>;
+}
+
+/// Names accepted by `--strategy`.
+pub const STRATEGY_NAMES: [&str; 2] = ["random", "deduction"];
+
+pub fn by_name(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "random" => Some(Box::new(RandomStrategy::new())),
+        "deduction" => Some(Box::new(DeductionStrategy::new())),
+        _ => None,
+    }
+}
+
+/// Reveals a random hidden, unflagged cell each turn.
+pub struct RandomStrategy {
+    rng: StdRng,
+}
+
+impl RandomStrategy {
+    pub fn new() -> Self {
+        Self { rng: StdRng::from_os_rng() }
+    }
+}
+
+impl Default for RandomStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn next_move(&mut self, game: &Game) -> Option<Move> {
+        random_hidden(game, &mut self.rng).map(|(x, y)| Move::Reveal(x, y))
+    }
+}
+
+/// Chords satisfied numbers, then plays whatever the solver can prove, and
+/// guesses randomly only when stuck.
+pub struct DeductionStrategy {
+    rng: StdRng,
+}
+
+impl DeductionStrategy {
+    pub fn new() -> Self {
+        Self { rng: StdRng::from_os_rng() }
+    }
+}
+
+impl Default for DeductionStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for DeductionStrategy {
+    fn next_move(&mut self, game: &Game) -> Option<Move> {
+        let opened = game.cells.iter().any(|c| c.state == CellState::Revealed);
+        if !opened {
+            return Some(Move::Reveal(game.width / 2, game.height / 2));
+        }
+        for y in 0..game.height {
+            for x in 0..game.width {
+                let cell = game.get(x, y);
+                if cell.state != CellState::Revealed || cell.neighbor_mines == 0 {
+                    continue;
+                }
+                let marked = game.neighbors(x, y).filter(|&(nx, ny)| game.get(nx, ny).marked_mine()).count();
+                let hidden = game.neighbors(x, y).any(|(nx, ny)| game.get(nx, ny).covered());
+                if marked == cell.neighbor_mines as usize && hidden {
+                    return Some(Move::Chord(x, y));
+                }
+            }
+        }
+        if let Some(deduction) = game.deduce().first() {
+            return Some(match *deduction {
+                Deduction::Safe(x, y) => Move::Reveal(x, y),
+                Deduction::Mine(x, y) => Move::Flag(x, y),
+            });
+        }
+        random_hidden(game, &mut self.rng).map(|(x, y)| Move::Reveal(x, y))
+    }
+}
+
+fn random_hidden(game: &Game, rng: &mut StdRng) -> Option<(usize, usize)> {
+    let hidden: Vec<_> = (0..game.height)
+        .flat_map(|y| (0..game.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| game.get(x, y).covered())
+        .collect();
+    hidden.choose(rng).copied()
+}
@@ -0,0 +1,249 @@
+use std::{
+    env,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tui::style::Color;
+
+/// Which set of colors the terminal can show well.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Palette {
+    /// The 16 ANSI colors. Safe everywhere, and the default whenever the
+    /// terminal doesn't say it supports more.
+    Basic,
+    /// 24-bit RGB, used when `COLORTERM` advertises it.
+    TrueColor,
+}
+
+impl Palette {
+    fn detect() -> Self {
+        match env::var("COLORTERM").as_deref() {
+            Ok("truecolor" | "24bit") => Palette::TrueColor,
+            _ => Palette::Basic,
+        }
+    }
+}
+
+/// The palette picked on the settings screen.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaletteChoice {
+    /// Whatever `COLORTERM` says.
+    #[default]
+    Auto,
+    Basic,
+    TrueColor,
+}
+
+impl PaletteChoice {
+    pub fn next(self) -> Self {
+        match self {
+            PaletteChoice::Auto => PaletteChoice::Basic,
+            PaletteChoice::Basic => PaletteChoice::TrueColor,
+            PaletteChoice::TrueColor => PaletteChoice::Auto,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PaletteChoice::Auto => "自动",
+            PaletteChoice::Basic => "16 色",
+            PaletteChoice::TrueColor => "真彩色",
+        }
+    }
+}
+
+/// Every color the game draws with, apart from the highlights that carry a
+/// meaning of their own (selection, hint, heat, planning, post-mortem).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Theme {
+    pub title: Color,
+    pub border: Color,
+    pub status: Color,
+    /// Background of the cell under the cursor.
+    pub cursor: Color,
+    /// Text on the cursor; `None` keeps the cell's color, lightened.
+    pub cursor_text: Option<Color>,
+    pub hidden: Color,
+    pub flag: Color,
+    pub question: Color,
+    pub mine: Color,
+    /// Colors of the numbers 1 to 8.
+    pub numbers: [Color; 8],
+    /// Tells cells apart by shape and brightness rather than hue alone:
+    /// flags are bold, open mines are `@` with the one stepped on reversed,
+    /// and the cursor is drawn reversed instead of on a background.
+    pub shapes: bool,
+}
+
+/// Names accepted by `--theme`, in the order of `THEMES`.
+pub const THEME_NAMES: [&str; 5] = ["classic", "dark", "high-contrast", "mono", "colorblind"];
+
+/// The themes as the settings screen lists them, in the same order.
+pub const THEME_LABELS: [&str; 5] = ["经典", "暗色", "高对比度", "单色", "色盲友好"];
+
+/// The built-in themes. Classic is the default and the colors the game has
+/// always had; the others dim it, brighten it, drop color altogether or,
+/// for colorblind players, keep to blues, yellows and purples and lean on
+/// `shapes`.
+pub const THEMES: [Theme; 5] = [
+    Theme {
+        title: Color::Yellow,
+        border: Color::Reset,
+        status: Color::Cyan,
+        cursor: Color::DarkGray,
+        cursor_text: None,
+        hidden: Color::White,
+        flag: Color::Red,
+        question: Color::Yellow,
+        mine: Color::Red,
+        numbers: [
+            Color::LightBlue,
+            Color::Green,
+            Color::LightRed,
+            Color::Blue,
+            Color::Red,
+            Color::Cyan,
+            Color::White,
+            Color::Gray,
+        ],
+        shapes: false,
+    },
+    Theme {
+        title: Color::Gray,
+        border: Color::DarkGray,
+        status: Color::Gray,
+        cursor: Color::DarkGray,
+        cursor_text: None,
+        hidden: Color::Gray,
+        flag: Color::LightRed,
+        question: Color::LightYellow,
+        mine: Color::LightRed,
+        numbers: [
+            Color::LightBlue,
+            Color::LightGreen,
+            Color::LightRed,
+            Color::LightMagenta,
+            Color::LightYellow,
+            Color::LightCyan,
+            Color::Gray,
+            Color::DarkGray,
+        ],
+        shapes: false,
+    },
+    Theme {
+        title: Color::White,
+        border: Color::White,
+        status: Color::White,
+        cursor: Color::Yellow,
+        cursor_text: Some(Color::Black),
+        hidden: Color::White,
+        flag: Color::LightRed,
+        question: Color::LightYellow,
+        mine: Color::LightRed,
+        numbers: [
+            Color::LightCyan,
+            Color::LightGreen,
+            Color::LightRed,
+            Color::LightMagenta,
+            Color::LightYellow,
+            Color::LightBlue,
+            Color::White,
+            Color::White,
+        ],
+        shapes: false,
+    },
+    Theme {
+        title: Color::White,
+        border: Color::Gray,
+        status: Color::Gray,
+        cursor: Color::White,
+        cursor_text: Some(Color::Black),
+        hidden: Color::Gray,
+        flag: Color::White,
+        question: Color::White,
+        mine: Color::White,
+        numbers: [Color::White; 8],
+        shapes: false,
+    },
+    Theme {
+        title: Color::Yellow,
+        border: Color::Reset,
+        status: Color::Cyan,
+        cursor: Color::Reset,
+        cursor_text: None,
+        hidden: Color::White,
+        flag: Color::LightYellow,
+        question: Color::LightCyan,
+        mine: Color::White,
+        numbers: [
+            Color::LightBlue,
+            Color::Yellow,
+            Color::LightMagenta,
+            Color::Blue,
+            Color::LightCyan,
+            Color::Magenta,
+            Color::White,
+            Color::Gray,
+        ],
+        shapes: true,
+    },
+];
+
+/// Index into `THEMES` of the theme in use.
+static THEME: AtomicU8 = AtomicU8::new(0);
+
+/// Switches to the theme at `index` in `THEMES` from the next frame on.
+pub fn choose_theme(index: usize) {
+    THEME.store(index.min(THEMES.len() - 1) as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> &'static Theme {
+    &THEMES[THEME.load(Ordering::Relaxed) as usize]
+}
+
+/// The current `PaletteChoice`, as its discriminant. Read once per drawn
+/// cell, so an atomic rather than a lock.
+static CHOICE: AtomicU8 = AtomicU8::new(PaletteChoice::Auto as u8);
+
+/// Switches the palette from the next frame on.
+pub fn choose(choice: PaletteChoice) {
+    CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// The palette for this terminal: the one chosen, or else the one detected
+/// on first use.
+pub fn palette() -> Palette {
+    static PALETTE: OnceLock<Palette> = OnceLock::new();
+    match CHOICE.load(Ordering::Relaxed) {
+        choice if choice == PaletteChoice::Basic as u8 => Palette::Basic,
+        choice if choice == PaletteChoice::TrueColor as u8 => Palette::TrueColor,
+        _ => *PALETTE.get_or_init(Palette::detect),
+    }
+}
+
+/// The color to actually draw for one of the board's basic colors. On a
+/// truecolor terminal the ANSI colors, whose exact shade depends on the
+/// terminal's theme, are swapped for fixed, clearer ones; anything without
+/// a replacement is drawn as is.
+pub fn resolve(color: Color) -> Color {
+    if palette() == Palette::Basic {
+        return color;
+    }
+    match color {
+        Color::Blue => Color::Rgb(66, 133, 244),
+        Color::Green => Color::Rgb(52, 168, 83),
+        Color::Red => Color::Rgb(234, 67, 53),
+        Color::LightBlue => Color::Rgb(138, 180, 248),
+        Color::LightGreen => Color::Rgb(129, 201, 149),
+        Color::LightRed => Color::Rgb(242, 139, 130),
+        Color::LightMagenta => Color::Rgb(200, 120, 255),
+        Color::White => Color::Rgb(230, 230, 230),
+        Color::DarkGray => Color::Rgb(68, 68, 68),
+        other => other,
+    }
+}
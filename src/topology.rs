@@ -0,0 +1,41 @@
+use crate::Game;
+
+/// How cells touch. Everything that walks neighbors (counts, flood fill,
+/// chords, the solver) goes through `Game::neighbors`, so the rules are the
+/// same on either grid.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Topology {
+    /// Eight neighbors: the surrounding 3x3.
+    Square,
+    /// Six neighbors on a hex grid stored in "odd-r" offset rows: every odd
+    /// row sits half a cell to the right of the rows above and below it.
+    Hex,
+}
+
+impl Topology {
+    /// Offsets to the neighbors of a cell in row `y`.
+    fn offsets(self, y: usize) -> &'static [(i32, i32)] {
+        match self {
+            Topology::Square => &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)],
+            Topology::Hex if y.is_multiple_of(2) => &[(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)],
+            Topology::Hex => &[(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+        }
+    }
+}
+
+impl Game {
+    /// The cells touching `(x, y)` under the board's topology. Lazy, so the
+    /// hot loops (counts, flood fill, the solver) don't allocate per cell;
+    /// collect it to keep it across changes to the board.
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.config
+            .topology
+            .offsets(y)
+            .iter()
+            .filter_map(move |&(dx, dy)| {
+                let nx = x.checked_add_signed(dx as isize)?;
+                let ny = y.checked_add_signed(dy as isize)?;
+                (nx < self.width && ny < self.height).then_some((nx, ny))
+            })
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{CellState, Game};
+
+/// A cell as the player sees it: everything but where the hidden mines are.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Seen {
+    Hidden,
+    Flagged,
+    Questioned,
+    /// A revealed safe cell and its neighbor count, `0` included.
+    Number(u8),
+    /// A mine that was stepped on.
+    Mine,
+}
+
+/// The whole board as `Seen` cells, row by row like `Game::cells`, for bots
+/// and tests that must not peek.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BoardView {
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Seen>,
+}
+
+impl BoardView {
+    pub fn get(&self, x: usize, y: usize) -> Seen {
+        self.cells[y * self.width + x]
+    }
+}
+
+impl Game {
+    /// Hidden, flagged, questioned or revealed, which a player can always see.
+    pub fn cell_state(&self, x: usize, y: usize) -> CellState {
+        self.get(x, y).state
+    }
+
+    /// Whether `(x, y)` is open and shows a number other than `0`.
+    pub fn is_revealed_number(&self, x: usize, y: usize) -> bool {
+        matches!(self.seen(x, y), Seen::Number(count) if count > 0)
+    }
+
+    /// The cell at `(x, y)` as the player sees it.
+    pub fn seen(&self, x: usize, y: usize) -> Seen {
+        let cell = self.get(x, y);
+        match cell.state {
+            CellState::Hidden => Seen::Hidden,
+            CellState::Flagged => Seen::Flagged,
+            CellState::Questioned => Seen::Questioned,
+            CellState::Revealed if cell.is_mine => Seen::Mine,
+            CellState::Revealed => Seen::Number(cell.neighbor_mines),
+        }
+    }
+
+    /// Every cell as `seen` gives it.
+    pub fn board_view(&self) -> BoardView {
+        BoardView {
+            width: self.width,
+            height: self.height,
+            cells: self.iter_cells().map(|(x, y, _)| self.seen(x, y)).collect(),
+        }
+    }
+}
@@ -0,0 +1,598 @@
+use minesweeper::{
+    strategy::Move, topology::Topology, view::Seen, AutoFirst, CellState, Game, GameConfig, MineTrigger, WinCondition,
+};
+
+/// A 4x3 board with mines in two opposite corners:
+///
+/// ```text
+/// * 1 . .
+/// 1 1 1 1
+/// . . 1 *
+/// ```
+fn corners() -> Game {
+    Game::with_mines(4, 3, &[(0, 0), (3, 2)]).unwrap()
+}
+
+#[test]
+fn neighbor_counts() {
+    let game = corners();
+    let counts: Vec<Vec<u8>> = game.rows().map(|row| row.iter().map(|c| c.neighbor_mines).collect()).collect();
+    assert_eq!(counts[0][1..], [1, 0, 0]);
+    assert_eq!(counts[1], [1, 1, 1, 1]);
+    assert_eq!(counts[2][..3], [0, 0, 1]);
+}
+
+#[test]
+fn opening_every_safe_cell_wins() {
+    let mut game = corners();
+    game.reveal(3, 0);
+    assert!(!game.victory);
+    assert_eq!(game.get(1, 0).state, CellState::Revealed);
+    assert_eq!(game.get(0, 2).state, CellState::Hidden);
+
+    game.reveal(0, 2);
+    assert!(game.victory);
+    assert!(!game.game_over);
+    assert_eq!(game.safe_left(), 0);
+}
+
+#[test]
+fn stepping_on_a_mine_loses() {
+    let mut game = corners();
+    game.reveal(3, 0);
+    game.reveal(0, 0);
+    assert!(game.game_over);
+    assert!(!game.victory);
+}
+
+#[test]
+fn auto_flag_flags_forced_mines_once() {
+    // * 1 .
+    let mut game = Game::with_mines(3, 1, &[(0, 0)]).unwrap();
+    assert_eq!(game.auto_flag(), 0);
+    game.reveal(2, 0);
+    assert_eq!(game.auto_flag(), 1);
+    assert_eq!(game.get(0, 0).state, CellState::Flagged);
+    assert_eq!(game.auto_flag(), 0);
+    assert_eq!(game.flags, 1);
+}
+
+#[test]
+fn hints_point_out_a_safe_cell_then_open_it() {
+    // * 1
+    // 1 1
+    // . .
+    // Either top cell could be the mine, and a flag doesn't settle it.
+    let mut game = Game::with_mines(2, 3, &[(0, 0)]).unwrap();
+    game.reveal(0, 2);
+    assert_eq!(game.find_safe_cell(), None);
+    assert!(!game.ask_hint());
+    Move::Flag(0, 0).apply(&mut game);
+    assert_eq!(game.find_safe_cell(), None);
+
+    // . * 1
+    // The 1 has only the mine hidden beside it, which leaves the mine total
+    // nothing for the far cell.
+    let mut game = Game::with_mines(4, 1, &[(1, 0)]).unwrap();
+    game.reveal(3, 0);
+    assert_eq!(game.find_safe_cell(), Some((0, 0)));
+    assert!(game.ask_hint());
+    assert_eq!(game.hint, Some((0, 0)));
+    assert_eq!((game.cursor_x, game.cursor_y), (0, 0));
+    assert_eq!(game.hints_used, 1);
+
+    assert!(game.ask_hint());
+    assert!(game.victory);
+    assert_eq!(game.hints_used, 1);
+}
+
+#[test]
+fn three_bv_counts_openings_and_lone_numbers() {
+    // Both openings reach every number.
+    assert_eq!(corners().calculate_3bv(), 2);
+    // A mine in the middle leaves eight numbers and no opening.
+    assert_eq!(Game::with_mines(3, 3, &[(1, 1)]).unwrap().calculate_3bv(), 8);
+    // One opening left of the mines, and the two numbers right of them.
+    let game = Game::with_mines(5, 2, &[(3, 0), (3, 1)]).unwrap();
+    assert_eq!(game.calculate_3bv(), 3);
+}
+
+#[test]
+fn only_moves_that_change_the_board_count_as_clicks() {
+    let mut game = corners();
+    Move::Reveal(3, 0).apply(&mut game);
+    Move::Reveal(3, 0).apply(&mut game);
+    Move::Flag(2, 1).apply(&mut game);
+    // No flag around the 1 yet, so the chord opens nothing.
+    Move::Chord(1, 1).apply(&mut game);
+    assert_eq!(game.clicks, 1);
+
+    Move::Flag(0, 0).apply(&mut game);
+    Move::Chord(1, 1).apply(&mut game);
+    assert!(game.victory);
+    assert_eq!(game.clicks, 3);
+}
+
+#[test]
+fn flag_cycles_keep_the_count_and_may_go_past_the_mines() {
+    let mut game = corners();
+    for _ in 0..4 {
+        // Flagged, questioned, then hidden again.
+        for (expected, state) in [(1, CellState::Flagged), (0, CellState::Questioned), (0, CellState::Hidden)] {
+            Move::Flag(1, 0).apply(&mut game);
+            assert_eq!(game.flags, expected);
+            assert_eq!(game.get(1, 0).state, state);
+        }
+    }
+
+    for (x, y) in [(1, 0), (2, 0), (3, 0)] {
+        Move::Flag(x, y).apply(&mut game);
+    }
+    assert_eq!(game.flags, 3);
+    assert_eq!(game.remaining_mines(), -1);
+}
+
+#[test]
+fn a_board_without_mines_is_won_by_the_first_reveal() {
+    let mut game = Game::new_zen(9, 9).unwrap();
+    assert_eq!(game.mines, 0);
+    assert!(!game.config.timer);
+    game.reveal(4, 4);
+    assert!(game.victory);
+    assert_eq!(game.safe_left(), 0);
+    assert_eq!(game.calculate_3bv(), 1);
+}
+
+#[test]
+fn a_number_between_two_hidden_cells_gives_even_odds() {
+    // * 1 .
+    // 1 1 .
+    let mut game = Game::with_mines(3, 2, &[(0, 0)]).unwrap();
+    game.reveal(2, 0);
+    let odds = game.mine_probabilities();
+    assert_eq!(odds.len(), 2);
+    assert_eq!(odds[&(0, 0)], 0.5);
+    assert_eq!(odds[&(0, 1)], 0.5);
+}
+
+#[test]
+fn auto_complete_flags_the_mines_left_on_a_win() {
+    let mut game = corners();
+    game.config = game.config.clone().auto_complete(true);
+    game.reveal(3, 0);
+    assert_eq!(game.flags, 0);
+    game.reveal(0, 2);
+    assert!(game.victory);
+    assert_eq!(game.flags, 2);
+    assert_eq!(game.get(0, 0).state, CellState::Flagged);
+    assert_eq!(game.get(3, 2).state, CellState::Flagged);
+}
+
+#[test]
+fn to_ascii_shows_the_solution_whatever_is_revealed() {
+    let mut game = corners();
+    let grid = "*100\n1111\n001*\n";
+    assert_eq!(game.to_ascii(), grid);
+    game.reveal(3, 0);
+    assert_eq!(game.to_ascii(), grid);
+}
+
+#[test]
+fn display_draws_what_the_player_sees_and_the_alternate_form_everything() {
+    let mut game = Game::with_mines(3, 2, &[(0, 0)]).unwrap();
+    game.reveal(2, 1);
+    Move::Flag(0, 0).apply(&mut game);
+    assert_eq!(format!("{}", game), "⚑1 \n■1 \n3x2  旗帜 1/1  剩余安全格 1  进行中");
+    assert_eq!(format!("{:#}", game), "*1 \n11 \n3x2  旗帜 1/1  剩余安全格 1  进行中");
+}
+
+#[test]
+fn from_ascii_reads_back_what_to_ascii_writes() {
+    let game = corners();
+    let loaded = Game::from_ascii(&game.to_ascii()).unwrap();
+    assert_eq!((loaded.width, loaded.height, loaded.mines), (4, 3, 2));
+    assert_eq!(loaded.to_ascii(), game.to_ascii());
+
+    // The numbers are worked out again, so dots will do.
+    let drawn = Game::from_ascii("*...\n....\n...*\n\n").unwrap();
+    assert_eq!(drawn.to_ascii(), game.to_ascii());
+}
+
+#[test]
+fn from_ascii_names_what_is_wrong() {
+    assert!(Game::from_ascii("").is_err());
+    assert_eq!(Game::from_ascii("*..\n..").err().unwrap(), "第 2 行有 2 格，第 1 行有 3 格");
+    assert_eq!(Game::from_ascii("*..\n.x.").err().unwrap(), "第 2 行第 2 格: 无法识别的字符 'x'");
+}
+
+#[test]
+fn safe_left_counts_down_with_every_safe_cell_opened() {
+    let mut game = corners();
+    assert_eq!((game.safe_cells(), game.safe_left()), (10, 10));
+    game.reveal(3, 0);
+    assert_eq!(game.safe_left(), 10 - game.revealed_safe);
+    game.reveal(0, 2);
+    assert_eq!(game.safe_left(), 0);
+    assert!(game.victory);
+}
+
+#[test]
+fn undo_takes_back_only_the_last_flag_move() {
+    let mut game = corners();
+    Move::Flag(0, 0).apply(&mut game);
+    Move::Flag(0, 0).apply(&mut game);
+    assert_eq!((game.get(0, 0).state, game.flags), (CellState::Questioned, 0));
+    Move::UndoFlag(0, 0).apply(&mut game);
+    assert_eq!((game.get(0, 0).state, game.flags), (CellState::Flagged, 1));
+    // One step only.
+    assert_eq!(game.last_flag, None);
+    Move::UndoFlag(0, 0).apply(&mut game);
+    assert_eq!(game.get(0, 0).state, CellState::Flagged);
+
+    Move::Flag(3, 2).apply(&mut game);
+    Move::Reveal(3, 0).apply(&mut game);
+    Move::UndoFlag(3, 2).apply(&mut game);
+    assert_eq!((game.get(3, 2).state, game.flags), (CellState::Flagged, 2));
+}
+
+/// Which cells are open, one string per row: `o` open, `.` not.
+fn opened(game: &Game) -> Vec<String> {
+    game.rows()
+        .map(|row| row.iter().map(|c| if c.state == CellState::Revealed { 'o' } else { '.' }).collect())
+        .collect()
+}
+
+#[test]
+fn a_zero_opens_the_whole_board_but_the_lone_mine() {
+    let mut game = Game::with_mines(5, 4, &[(0, 0)]).unwrap();
+    game.reveal(4, 3);
+    assert_eq!(opened(&game), [".oooo", "ooooo", "ooooo", "ooooo"]);
+    let around = [(1, 0), (0, 1), (1, 1)].map(|(x, y)| game.get(x, y).neighbor_mines);
+    assert_eq!(around, [1, 1, 1]);
+    assert_eq!(game.get(2, 0).neighbor_mines, 0);
+    assert!(game.victory);
+}
+
+#[test]
+fn a_number_opens_only_itself() {
+    let mut game = Game::with_mines(5, 4, &[(0, 0)]).unwrap();
+    game.reveal(1, 1);
+    assert_eq!(opened(&game), [".....", ".o...", ".....", "....."]);
+    assert!(game.pending.is_empty());
+}
+
+#[test]
+fn the_cascade_stops_at_the_numbers_along_a_wall_of_mines() {
+    // . . 2 * 2
+    // . . 3 * 3
+    // . . 2 * 2
+    let mut game = Game::with_mines(5, 3, &[(3, 0), (3, 1), (3, 2)]).unwrap();
+    game.reveal(0, 0);
+    assert_eq!(opened(&game), ["ooo..", "ooo..", "ooo.."]);
+    let edge: Vec<u8> = game.rows().map(|row| row[2].neighbor_mines).collect();
+    assert_eq!(edge, [2, 3, 2]);
+    assert!(!game.victory);
+}
+
+#[test]
+fn the_cascade_goes_around_flags_without_opening_them() {
+    let mut game = Game::with_mines(4, 2, &[]).unwrap();
+    Move::Flag(2, 0).apply(&mut game);
+    game.reveal(0, 0);
+    assert_eq!(opened(&game), ["oo.o", "oooo"]);
+    assert_eq!(game.get(2, 0).state, CellState::Flagged);
+}
+
+#[test]
+fn a_board_needs_at_least_one_safe_cell() {
+    assert!(Game::new(3, 3, 9).is_err());
+    assert!(Game::new(3, 3, 10).is_err());
+    assert!(Game::with_mines(2, 1, &[(0, 0), (1, 0)]).is_err());
+    assert_eq!(Game::new(3, 3, 8).unwrap().mines, 8);
+}
+
+#[test]
+fn cells_are_stored_row_by_row() {
+    let mut game = corners();
+    assert_eq!(game.cells.len(), 12);
+    assert!(game.cells[0].is_mine && game.cells[11].is_mine);
+    game.get_mut(1, 2).state = CellState::Flagged;
+    assert_eq!(game.cells[2 * 4 + 1].state, CellState::Flagged);
+    assert_eq!(game.rows().map(<[_]>::len).collect::<Vec<_>>(), [4, 4, 4]);
+}
+
+#[test]
+fn neighbors_stay_on_the_board_and_leave_out_the_cell_itself() {
+    let game = Game::new(4, 3, 1).unwrap();
+    let sorted = |x, y| {
+        let mut around = game.neighbors(x, y).collect::<Vec<_>>();
+        around.sort_by_key(|&(x, y)| (y, x));
+        around
+    };
+    assert_eq!(sorted(0, 0), [(1, 0), (0, 1), (1, 1)]);
+    assert_eq!(sorted(3, 2), [(2, 1), (3, 1), (2, 2)]);
+    assert_eq!(sorted(2, 0), [(1, 0), (3, 0), (1, 1), (2, 1), (3, 1)]);
+    assert_eq!(sorted(1, 1), [(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    assert_eq!(Game::new(1, 1, 0).unwrap().neighbors(0, 0).count(), 0);
+}
+
+#[test]
+fn hex_rows_lean_left_when_even_and_right_when_odd() {
+    let config = GameConfig::default().size(4, 4).topology(Topology::Hex);
+    let game = Game::with_mines_in(config, &[(2, 0)]).unwrap();
+    let sorted = |x, y| {
+        let mut around = game.neighbors(x, y).collect::<Vec<_>>();
+        around.sort_by_key(|&(x, y)| (y, x));
+        around
+    };
+    assert_eq!(sorted(1, 2), [(0, 1), (1, 1), (0, 2), (2, 2), (0, 3), (1, 3)]);
+    assert_eq!(sorted(1, 1), [(1, 0), (2, 0), (0, 1), (2, 1), (1, 2), (2, 2)]);
+    assert_eq!(sorted(0, 0), [(1, 0), (0, 1)]);
+    assert_eq!(sorted(3, 1), [(3, 0), (2, 1), (3, 2)]);
+    // Of the cells below the mine, only those an odd row puts beneath it
+    // touch it; (3,1) would on a square grid.
+    let count = |x, y| game.get(x, y).neighbor_mines;
+    assert_eq!([count(1, 0), count(3, 0), count(1, 1), count(2, 1), count(3, 1)], [1, 1, 1, 1, 0]);
+    assert_eq!(count(1, 2), 0);
+}
+
+#[test]
+fn the_assist_keeps_the_first_row_and_column_clear() {
+    for seed in 0..20 {
+        let config = GameConfig::default().size(6, 5).mines(19).seed(seed).safe_edge(true);
+        let game = Game::from_config(config).unwrap();
+        assert!(game.iter_cells().all(|(x, y, cell)| !cell.is_mine || (x > 0 && y > 0)));
+        assert_eq!(game.cells.iter().filter(|c| c.is_mine).count(), 19);
+    }
+    assert!(Game::from_config(GameConfig::default().size(6, 5).mines(20).safe_edge(true)).is_err());
+}
+
+#[test]
+fn the_board_view_shows_only_what_the_player_sees() {
+    let mut game = corners();
+    game.reveal(3, 0);
+    Move::Flag(0, 0).apply(&mut game);
+    let view = game.board_view();
+    assert_eq!(view.get(0, 0), Seen::Flagged);
+    assert_eq!(view.get(2, 0), Seen::Number(0));
+    assert_eq!(view.get(1, 1), Seen::Number(1));
+    assert_eq!(view.get(3, 2), Seen::Hidden);
+    assert!(game.is_revealed_number(1, 1) && !game.is_revealed_number(2, 0));
+    assert_eq!(game.cell_state(0, 2), CellState::Hidden);
+}
+
+/// A 4x2 board with mines in the top corners, under `trigger`, with a safe
+/// cell already open so the next reveal can hit a mine:
+///
+/// ```text
+/// * . . *
+/// 1 . . 1
+/// ```
+fn under(trigger: MineTrigger) -> Game {
+    let config = GameConfig::default().size(4, 2).mine_trigger(trigger);
+    let mut game = Game::with_mines_in(config, &[(0, 0), (3, 0)]).unwrap();
+    game.reveal(0, 1);
+    assert!(!game.game_over);
+    game
+}
+
+#[test]
+fn a_mine_ends_the_game_by_default() {
+    let mut game = under(MineTrigger::Lose);
+    game.reveal(0, 0);
+    assert!(game.game_over && !game.victory);
+    assert_eq!(game.get(0, 0).state, CellState::Revealed);
+    assert_eq!(game.exploded, [(0, 0)]);
+    assert!(game.end_time.is_some());
+}
+
+#[test]
+fn a_spare_life_keeps_the_game_going() {
+    let mut game = under(MineTrigger::Forgiving { lives: 2 });
+    assert_eq!(game.lives, 2);
+    game.reveal(0, 0);
+    assert!(!game.game_over);
+    assert_eq!(game.lives, 1);
+    assert_eq!(game.get(0, 0).state, CellState::Revealed);
+    game.reveal(1, 1);
+    assert_eq!(game.get(1, 1).state, CellState::Revealed);
+    game.reveal(3, 0);
+    assert!(game.game_over);
+    assert_eq!((game.lives, game.exploded.len()), (0, 2));
+}
+
+#[test]
+fn practice_counts_mines_but_never_ends_the_game() {
+    let mut game = under(MineTrigger::Practice);
+    game.reveal(0, 0);
+    game.reveal(3, 0);
+    assert!(!game.game_over);
+    assert_eq!(game.exploded, [(0, 0), (3, 0)]);
+    for (x, y) in [(1, 0), (2, 0), (1, 1), (2, 1), (3, 1)] {
+        game.reveal(x, y);
+    }
+    assert!(game.victory && game.practice_completion());
+}
+
+#[test]
+fn zen_flags_the_mine_instead_of_opening_it() {
+    let mut game = under(MineTrigger::Zen);
+    game.reveal(0, 0);
+    assert!(!game.game_over);
+    assert_eq!(game.get(0, 0).state, CellState::Flagged);
+    assert_eq!(game.flags, 1);
+    assert!(game.exploded.is_empty());
+}
+
+/// `under`'s board, won by flagging its two mines.
+fn by_flags() -> Game {
+    let config = GameConfig::default().size(4, 2).win_condition(WinCondition::FlagMines);
+    let mut game = Game::with_mines_in(config, &[(0, 0), (3, 0)]).unwrap();
+    game.reveal(0, 1);
+    game
+}
+
+#[test]
+fn flagging_exactly_the_mines_wins() {
+    let mut game = by_flags();
+    Move::Flag(0, 0).apply(&mut game);
+    assert!(!game.victory);
+    Move::Flag(3, 0).apply(&mut game);
+    assert!(game.victory);
+}
+
+#[test]
+fn an_extra_flag_keeps_the_flag_win_away() {
+    let mut game = by_flags();
+    for (x, y) in [(1, 0), (0, 0), (3, 0)] {
+        Move::Flag(x, y).apply(&mut game);
+    }
+    assert!(!game.victory);
+    // Taking the extra flag off settles it.
+    Move::Flag(1, 0).apply(&mut game);
+    assert!(game.victory);
+}
+
+#[test]
+fn a_flag_on_the_wrong_cell_keeps_the_flag_win_away() {
+    let mut game = by_flags();
+    Move::Flag(0, 0).apply(&mut game);
+    Move::Flag(2, 0).apply(&mut game);
+    assert_eq!(game.flags, game.mines);
+    assert!(!game.victory && !game.game_over);
+}
+
+#[test]
+fn a_safe_reveal_moves_the_mine_away_then_runs_out() {
+    // The seed picks where the mine goes; some places would let (0,0)
+    // open every safe cell and win.
+    let config = GameConfig::default().size(4, 2).seed(2).safe_reveals(1);
+    let mut game = Game::with_mines_in(config, &[(0, 0), (3, 0)]).unwrap();
+    game.reveal(0, 1);
+    Move::SafeReveal(0, 0).apply(&mut game);
+    assert!(!game.game_over && !game.victory);
+    assert!(!game.get(0, 0).is_mine);
+    assert_eq!(game.get(0, 0).state, CellState::Revealed);
+    assert!(game.cells.iter().all(|cell| !(cell.is_mine && cell.state == CellState::Revealed)));
+    assert!(game.exploded.is_empty());
+    assert_eq!(game.mines, 2);
+    assert_eq!(game.cells.iter().filter(|cell| cell.is_mine).count(), 2);
+    assert_eq!(game.safe_reveals, 0);
+
+    // Spent: the next mine is hit like any other.
+    let (x, y, _) = game.iter_cells().find(|(_, _, cell)| cell.is_mine && cell.covered()).unwrap();
+    Move::SafeReveal(x, y).apply(&mut game);
+    assert!(game.game_over && !game.victory);
+    assert_eq!(game.exploded, [(x, y)]);
+}
+
+#[test]
+fn a_big_opening_always_starts_on_a_blank_that_spreads() {
+    for (width, height, mines) in [(9, 9, 10), (16, 16, 40), (30, 16, 99)] {
+        for seed in 0..40 {
+            let config = GameConfig::default().size(width, height).mines(mines).seed(seed).big_opening(true);
+            let mut game = Game::from_config(config).unwrap();
+            let (x, y) = (seed as usize * 7 % width, seed as usize * 5 % height);
+            game.reveal(x, y);
+            let first = game.get(x, y);
+            assert!(!first.is_mine && first.neighbor_mines == 0, "seed {} at ({}, {})", seed, x, y);
+            assert!(game.revealed_safe > 1, "seed {} opened a single cell", seed);
+            assert_eq!(game.cells.iter().filter(|c| c.is_mine).count(), mines);
+        }
+    }
+}
+
+#[test]
+fn auto_first_deals_a_board_already_opened() {
+    for auto_first in [AutoFirst::Waiting, AutoFirst::Timed] {
+        for seed in 0..20 {
+            let config = GameConfig::default().size(9, 9).mines(10).seed(seed).auto_first(auto_first);
+            let mut game = Game::from_config(config).unwrap();
+            for _ in 0..2 {
+                // Each of these boards has a blank, and the start is one.
+                assert!(game.revealed_safe > 1, "seed {} opened {} cells", seed, game.revealed_safe);
+                assert!(!game.game_over && game.exploded.is_empty());
+                assert_eq!(game.start_time.is_some(), auto_first == AutoFirst::Timed);
+                // A new board from `reset` is opened the same way.
+                game.reset();
+            }
+        }
+    }
+}
+
+/// Mine neighbors per mine, averaged over the first 30 seeds of an
+/// intermediate board.
+fn mine_adjacency(clustering: f64) -> f64 {
+    let mut touching = 0;
+    for seed in 0..30 {
+        let config = GameConfig::default().size(16, 16).mines(40).seed(seed).clustering(clustering);
+        let game = Game::from_config(config).unwrap();
+        for (x, y, _) in game.iter_cells().filter(|(_, _, cell)| cell.is_mine) {
+            touching += game.neighbors(x, y).filter(|&(nx, ny)| game.get(nx, ny).is_mine).count();
+        }
+    }
+    touching as f64 / (30 * 40) as f64
+}
+
+#[test]
+fn clustering_puts_mines_next_to_each_other() {
+    let uniform = mine_adjacency(0.0);
+    let clustered = mine_adjacency(1.0);
+    assert!(clustered > uniform, "{} with clustering, {} without", clustered, uniform);
+    assert!(mine_adjacency(-1.0) < uniform);
+}
+
+#[test]
+fn a_custom_config_reaches_the_board() {
+    let config = GameConfig::default().size(12, 7).mines(20).seed(99);
+    let game = Game::from_config(config.clone()).unwrap();
+    assert_eq!((game.width, game.height, game.mines, game.seed), (12, 7, 20, 99));
+    assert_eq!(game.cells.len(), 12 * 7);
+    assert_eq!(game.cells.iter().filter(|c| c.is_mine).count(), 20);
+    // The seed picks the layout.
+    assert!(Game::from_config(config.clone()).unwrap().cells == game.cells);
+    let other = Game::from_config(config.seed(100)).unwrap();
+    assert!(other.cells != game.cells);
+}
+
+#[test]
+fn reset_deals_the_seeded_board_again_in_the_same_grid() {
+    let config = GameConfig::default().size(9, 9).mines(10).seed(42).mine_trigger(MineTrigger::Forgiving { lives: 1 });
+    let mut game = Game::from_config(config.clone()).unwrap();
+    game.reveal(4, 4);
+    let (x, y, _) = game.iter_cells().find(|(_, _, cell)| cell.is_mine && cell.covered()).unwrap();
+    game.reveal(x, y);
+    Move::Flag(0, 0).apply(&mut game);
+    game.cursor_x = 3;
+    let grid = game.cells.as_ptr();
+
+    game.reset();
+    let fresh = Game::from_config(config).unwrap();
+    assert!(game.cells == fresh.cells);
+    assert_eq!(game.cells.as_ptr(), grid);
+    assert_eq!(
+        (game.seed, game.mines, game.flags, game.revealed_safe, game.lives, game.clicks),
+        (fresh.seed, fresh.mines, fresh.flags, fresh.revealed_safe, fresh.lives, fresh.clicks)
+    );
+    assert_eq!((game.cursor_x, game.cursor_y), (fresh.cursor_x, fresh.cursor_y));
+    assert_eq!(game.exploded, fresh.exploded);
+    assert!(!game.game_over && !game.victory);
+    assert!(game.start_time.is_none() && game.end_time.is_none());
+}
+
+#[test]
+fn boards_over_the_cell_limit_are_refused() {
+    assert!(Game::from_config(GameConfig::default().size(100, 100).mines(10).max_cells(10_000)).is_ok());
+    let err = Game::from_config(GameConfig::default().size(100, 101).mines(10).max_cells(10_000)).err().unwrap();
+    assert!(err.contains("10000"), "{}", err);
+    assert!(Game::from_config(GameConfig::default().size(usize::MAX, 2).mines(1)).is_err());
+}
+
+#[test]
+fn a_config_without_a_safe_cell_is_refused() {
+    assert!(Game::from_config(GameConfig::default().size(4, 3).mines(11)).is_ok());
+    let err = Game::from_config(GameConfig::default().size(4, 3).mines(12)).err().unwrap();
+    assert!(err.contains("11"), "{}", err);
+    assert!(Game::from_config(GameConfig::default().size(4, 3).mines(13)).is_err());
+    assert!(Game::new(4, 3, 0).is_ok());
+}
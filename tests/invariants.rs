@@ -0,0 +1,37 @@
+use minesweeper::{strategy::Move, CellState, Game, GameConfig};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// What has to hold after any move on a board under the default rules.
+fn check(game: &Game, context: &str) {
+    let flagged = game.cells.iter().filter(|c| c.state == CellState::Flagged).count();
+    assert_eq!(game.flags, flagged, "{}: flag count", context);
+    let opened_safe = game.cells.iter().filter(|c| !c.is_mine && c.state == CellState::Revealed).count();
+    assert_eq!(game.revealed_safe, opened_safe, "{}: revealed count", context);
+    let mine_opened = game.cells.iter().any(|c| c.is_mine && c.state == CellState::Revealed);
+    assert_eq!(game.game_over, mine_opened, "{}: lost iff a mine is open", context);
+    let cleared = game.cells.iter().all(|c| c.is_mine || c.state == CellState::Revealed);
+    assert_eq!(game.victory, cleared && !mine_opened, "{}: won iff every safe cell is open", context);
+    assert!(!(game.victory && game.game_over), "{}: won and lost", context);
+}
+
+#[test]
+fn random_play_keeps_the_counters_and_the_outcome_in_step_with_the_board() {
+    for seed in 0..300 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (width, height) = (rng.random_range(1..=9), rng.random_range(1..=9));
+        let mines = rng.random_range(0..width * height);
+        let config = GameConfig::default().size(width, height).mines(mines).seed(seed);
+        let mut game = Game::from_config(config).unwrap();
+        for step in 0..80 {
+            let (x, y) = (rng.random_range(0..width), rng.random_range(0..height));
+            let next = match rng.random_range(0..10) {
+                0..=3 => Move::Reveal(x, y),
+                4..=7 => Move::Flag(x, y),
+                8 => Move::Chord(x, y),
+                _ => Move::UndoFlag(x, y),
+            };
+            next.apply(&mut game);
+            check(&game, &format!("seed {} step {} after {:?}", seed, step, next));
+        }
+    }
+}
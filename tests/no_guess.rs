@@ -0,0 +1,14 @@
+use minesweeper::{CellState, Game};
+
+#[test]
+fn opens_where_asked_on_a_board_that_needs_no_guess() {
+    let game = Game::new_no_guess(9, 9, 10, (4, 4)).unwrap();
+    assert_eq!(game.get(4, 4).state, CellState::Revealed);
+    assert!(!game.game_over);
+    assert!(game.generation.is_some_and(|report| report.solvable));
+}
+
+#[test]
+fn refuses_a_first_click_off_the_board() {
+    assert!(Game::new_no_guess(9, 9, 10, (9, 0)).is_err());
+}
@@ -0,0 +1,33 @@
+use minesweeper::scores::{Entry, Scores, Tally, LEADERBOARD_SIZE};
+
+#[test]
+fn a_loss_ends_the_streak_but_not_the_best_one() {
+    let mut tally = Tally::default();
+    assert_eq!(tally.win_rate(), None);
+    for won in [true, true, false, true] {
+        tally.record(won);
+    }
+    assert_eq!((tally.played, tally.won), (4, 3));
+    assert_eq!((tally.streak, tally.best_streak), (1, 2));
+    assert_eq!(tally.win_rate(), Some(0.75));
+}
+
+#[test]
+fn the_leaderboard_stays_sorted_and_keeps_the_fastest() {
+    let entry = |time: f64| Entry { name: format!("p{}", time), time, date: "2024-01-01".to_string() };
+    let mut scores = Scores::default();
+    for time in (1..=LEADERBOARD_SIZE).rev() {
+        assert!(scores.qualifies(1, time as f64));
+        scores.add_entry(1, entry(time as f64));
+    }
+    assert!(!scores.qualifies(1, LEADERBOARD_SIZE as f64));
+    assert!(scores.leaderboard[0].is_empty());
+
+    // A tie goes after the time already there.
+    assert_eq!(scores.add_entry(1, entry(3.0)), Some(3));
+    let times: Vec<f64> = scores.leaderboard[1].iter().map(|e| e.time).collect();
+    assert_eq!(times.len(), LEADERBOARD_SIZE);
+    assert_eq!(&times[..4], &[1.0, 2.0, 3.0, 3.0]);
+    assert_eq!(times.last(), Some(&(LEADERBOARD_SIZE as f64 - 1.0)));
+    assert_eq!(scores.add_entry(1, entry(100.0)), None);
+}
@@ -0,0 +1,175 @@
+use minesweeper::{solver::Deduction, strategy::Move, CellState, Game};
+
+/// A position drawn as text: `*` a hidden mine, `F` a flagged one, `X` one
+/// already stepped on, `.` a hidden safe cell and `o` an open one, showing
+/// its number.
+fn position(text: &str) -> Game {
+    let rows: Vec<&str> = text.lines().map(str::trim).filter(|row| !row.is_empty()).collect();
+    let mut game = Game::from_ascii(&rows.join("\n").replace(['F', 'X'], "*").replace('o', ".")).unwrap();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, letter) in row.chars().enumerate() {
+            match letter {
+                'o' => {
+                    game.get_mut(x, y).state = CellState::Revealed;
+                    game.revealed_safe += 1;
+                }
+                'F' => {
+                    game.get_mut(x, y).state = CellState::Flagged;
+                    game.flags += 1;
+                }
+                'X' => {
+                    game.get_mut(x, y).state = CellState::Revealed;
+                    game.exploded.push((x, y));
+                }
+                _ => {}
+            }
+        }
+    }
+    game
+}
+
+fn sorted(mut deductions: Vec<Deduction>) -> Vec<Deduction> {
+    deductions.sort();
+    deductions
+}
+
+#[test]
+fn one_two_one_has_its_mines_under_the_ones() {
+    // The 2 sees both mines; each 1 sees one of them and nothing else
+    // hidden can hold it.
+    let game = position(
+        "
+        .*.*.
+        .ooo.
+        ",
+    );
+    let expected = [
+        Deduction::Safe(0, 0),
+        Deduction::Safe(0, 1),
+        Deduction::Mine(1, 0),
+        Deduction::Safe(2, 0),
+        Deduction::Mine(3, 0),
+        Deduction::Safe(4, 0),
+        Deduction::Safe(4, 1),
+    ];
+    assert_eq!(sorted(game.deduce()), sorted(expected.to_vec()));
+}
+
+#[test]
+fn one_two_two_one_has_its_mines_under_the_twos() {
+    let game = position(
+        "
+        ..**..
+        .oooo.
+        ",
+    );
+    let expected = [
+        Deduction::Safe(0, 0),
+        Deduction::Safe(0, 1),
+        Deduction::Safe(1, 0),
+        Deduction::Mine(2, 0),
+        Deduction::Mine(3, 0),
+        Deduction::Safe(4, 0),
+        Deduction::Safe(5, 0),
+        Deduction::Safe(5, 1),
+    ];
+    assert_eq!(sorted(game.deduce()), sorted(expected.to_vec()));
+}
+
+#[test]
+fn a_number_inside_another_leaves_the_difference_to_it() {
+    // The 1 at (0,1) covers two of the 2's three cells, so (2,0) is a mine;
+    // then the 1 at (2,1) has its mine, clearing (1,0) and the cells to its
+    // right, and with (1,0) safe (0,0) is the first 1's mine. The board is
+    // wide enough for the mine total to stay out of it.
+    let game = position(
+        "
+        *.*...................*.......
+        ooo.........................*.
+        ",
+    );
+    let expected = [
+        Deduction::Mine(0, 0),
+        Deduction::Safe(1, 0),
+        Deduction::Mine(2, 0),
+        Deduction::Safe(3, 0),
+        Deduction::Safe(3, 1),
+    ];
+    assert_eq!(sorted(game.deduce()), sorted(expected.to_vec()));
+}
+
+#[test]
+fn the_mine_total_only_counts_once_few_cells_are_unknown() {
+    // No number says anything; only the total does, and its one mine has
+    // been stepped on.
+    // 24 unknown cells is the most it is used for.
+    let near_the_end = position(
+        "
+        X....
+        .....
+        .....
+        .....
+        .....
+        ",
+    );
+    let safe = near_the_end.deduce();
+    assert_eq!(safe.len(), 24);
+    assert!(safe.iter().all(|d| matches!(d, Deduction::Safe(..))));
+
+    let early = position(
+        "
+        X....
+        .....
+        .....
+        .....
+        .....
+        .....
+        ",
+    );
+    assert_eq!(early.deduce(), []);
+}
+
+#[test]
+fn a_frontier_too_long_to_search_is_left_out_rather_than_stalling() {
+    // A row of 1s between two hidden rows has more ways to lay its mines
+    // than the search budget allows.
+    let row: String = (0..60).map(|x| if x % 3 == 0 { '*' } else { '.' }).collect();
+    let long = position(&format!("{}\n{}\n{}\n{}\n", row, "o".repeat(60), ".".repeat(60), ".".repeat(60)));
+    assert_eq!(long.region_mine_bounds().len(), 0);
+    // Its cells get no chance, but the rest of the board still does, so a
+    // guess can still be offered.
+    let odds = long.mine_probabilities();
+    assert!(odds.keys().all(|&(_, y)| y == 3));
+    assert_eq!(odds.len(), 60);
+
+    // The same pattern, short enough, is worked out.
+    let short = position(
+        "
+        *..*..
+        oooooo
+        ......
+        ......
+        ",
+    );
+    let bounds = short.region_mine_bounds();
+    assert_eq!(bounds.len(), 1);
+    assert_eq!((bounds[0].first, bounds[0].cells), ((0, 0), 12));
+}
+
+#[test]
+fn a_wrong_flag_is_not_taken_for_a_mine() {
+    // The 1 at (2,0) has its mine at (1,0), but the player flagged (3,0).
+    // Trusting the flag would call the mine safe; ignoring it, only the
+    // mine total proves anything, and that is about (0,0).
+    let mut game = Game::with_mines(4, 1, &[(1, 0)]).unwrap();
+    Move::Reveal(2, 0).apply(&mut game);
+    Move::Flag(3, 0).apply(&mut game);
+    assert_eq!(game.find_safe_cell(), Some((0, 0)));
+    assert!(game.deduce().iter().all(|d| !matches!(d, Deduction::Safe(1, 0))));
+    assert_ne!(game.safest_guess(), Some((3, 0)));
+
+    game.ask_hint();
+    game.ask_hint();
+    assert!(!game.game_over);
+    assert_eq!(game.get(1, 0).state, CellState::Hidden);
+}
@@ -0,0 +1,91 @@
+use std::{thread, time::Duration};
+
+use minesweeper::{strategy::Move, CellState, Game};
+
+/// A 4x3 board with mines in two opposite corners, whose clock is started
+/// by opening the far corner and then set back `ago`.
+fn started(ago: Duration) -> Game {
+    let mut game = Game::with_mines(4, 3, &[(0, 0), (3, 2)]).unwrap();
+    game.reveal(3, 0);
+    game.start_time = game.start_time.and_then(|start| start.checked_sub(ago));
+    game
+}
+
+#[test]
+fn a_loss_keeps_the_time_it_lasted() {
+    let mut game = started(Duration::from_secs(5));
+    game.reveal(0, 0);
+    assert!(game.game_over);
+    let time = game.final_time();
+    assert!((5.0..6.0).contains(&time), "lost after 5 s, timed at {}", time);
+
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(game.final_time(), time);
+}
+
+#[test]
+fn a_win_keeps_the_time_it_lasted() {
+    let mut game = started(Duration::from_secs(5));
+    game.reveal(0, 2);
+    assert!(game.victory);
+    let time = game.final_time();
+    assert!((5.0..6.0).contains(&time), "won after 5 s, timed at {}", time);
+}
+
+#[test]
+fn a_win_on_the_first_reveal_still_takes_some_time() {
+    // No mines: the first reveal opens the board and ends it at once.
+    let mut empty = Game::new(5, 5, 0).unwrap();
+    empty.reveal(2, 2);
+    assert!(empty.victory);
+    assert!(empty.final_time() > 0.0);
+
+    // One mine in a corner, cleared by one big cascade.
+    let mut lucky = Game::with_mines(6, 6, &[(5, 5)]).unwrap();
+    lucky.reveal(0, 0);
+    assert!(lucky.victory);
+    assert!(lucky.final_time() > 0.0);
+}
+
+#[test]
+fn a_new_board_has_no_clock_running() {
+    assert!(Game::new(9, 9, 10).unwrap().start_time.is_none());
+    assert!(Game::with_mines(4, 3, &[(0, 0)]).unwrap().start_time.is_none());
+    let mut game = started(Duration::ZERO);
+    game.reset();
+    assert!(game.start_time.is_none());
+}
+
+#[test]
+fn the_first_reveal_starts_the_clock() {
+    let mut game = Game::with_mines(4, 3, &[(0, 0), (3, 2)]).unwrap();
+    Move::Reveal(3, 0).apply(&mut game);
+    let start = game.start_time.expect("started by the reveal");
+    Move::Reveal(0, 2).apply(&mut game);
+    assert_eq!(game.start_time, Some(start));
+}
+
+#[test]
+fn flags_leave_the_clock_alone() {
+    // As in the classic game, only opening cells counts as starting.
+    let mut game = Game::with_mines(4, 3, &[(0, 0), (3, 2)]).unwrap();
+    Move::Flag(0, 0).apply(&mut game);
+    Move::Flag(1, 1).apply(&mut game);
+    Move::Flag(1, 1).apply(&mut game);
+    assert_eq!(game.flags, 1);
+    assert!(game.start_time.is_none());
+}
+
+#[test]
+fn a_chord_starts_the_clock_when_nothing_else_has() {
+    // A number opened without a move, as a loaded position might have it.
+    // * 1 . .
+    let mut game = Game::with_mines(4, 1, &[(0, 0)]).unwrap();
+    game.get_mut(1, 0).state = CellState::Revealed;
+    game.get_mut(0, 0).state = CellState::Flagged;
+    game.flags = 1;
+    assert!(game.start_time.is_none());
+    Move::Chord(1, 0).apply(&mut game);
+    assert!(game.start_time.is_some());
+    assert_eq!(game.get(2, 0).state, CellState::Revealed);
+}